@@ -20,7 +20,7 @@ impl FilePosition {
     pub fn position(&self) -> (usize, usize) { self.position }
 
     pub fn compute_range(&self, image: &str) -> FileRange {
-        if !image.contains('\n') { return FileRange::new(self.path, self.position, (self.position.0, self.position.1 + image.len() - 1)); }
+        if !image.contains('\n') { return FileRange::new(self.path.clone(), self.position, (self.position.0, self.position.1 + image.len() - 1)); }
         let length = image.len();
         // if the image ends with a newline, we have to ignore it because it is included within the previous line
         let search = if image.ends_with('\n') { &image[..length - 2] } else { image };
@@ -28,7 +28,7 @@ impl FilePosition {
         let numBreaks = search.chars().filter(|&c| { c == '\n' }).count();
         // number of characters after the previous line break (use the real length here)
         let trailing = length - search.rfind('\n').expect("") - 1;
-        FileRange::new(self.path, self.position, (self.position.0 + numBreaks, trailing))
+        FileRange::new(self.path.clone(), self.position, (self.position.0 + numBreaks, trailing))
     }
 
     pub fn next_line(&self) -> FilePosition {
@@ -83,7 +83,7 @@ impl FileRange {
         } else if location.end.0 > self.end.0 || location.end.0 == self.end.0 && location.end.1 > self.end.1 {
             end = (location.end.0, location.end.1);
         }
-        FileRange::new(self.path, start, end)
+        FileRange::new(self.path.clone(), start, end)
     }
 }
 
@@ -103,12 +103,74 @@ pub enum DiagnosticLevel {
     Fatal = 5,
 }
 
+/// How safe a suggested fix is to apply without a human reviewing it,
+/// mirroring rustc's own `Applicability` classification.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Applicability {
+    /// The suggestion is known to be correct and can be applied mechanically.
+    MachineApplicable,
+    /// The suggestion is probably correct, but may not apply cleanly in every case.
+    MaybeIncorrect,
+    /// The suggestion contains placeholders (e.g. `/* type */`) that a human must fill in.
+    HasPlaceholders,
+}
+
+/// A proposed edit attached to a `Diagnostic`: replace the text at `span` with `replacement`.
+#[derive(Clone, Debug)]
+pub struct Suggestion {
+    pub span: FileRange,
+    pub replacement: String,
+    pub applicability: Applicability
+}
+
+impl Suggestion {
+    pub fn new(span: FileRange, replacement: String, applicability: Applicability) -> Suggestion {
+        Suggestion { span, replacement, applicability }
+    }
+}
+
+/// A stable identity attached to a `Diagnostic`, letting callers reference "what rule emitted
+/// this" independent of the (freely rewordable) message text - the vocabulary `renlang explain`
+/// looks code names up against, mirroring rustc's own `--explain REnnnn`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DiagnosticCode {
+    /// A hard compiler error, e.g. `RenError("REN0001")`.
+    RenError(&'static str),
+    /// A lint-level diagnostic, kept in its own namespace since a lint's code is never a hard
+    /// compile failure on its own.
+    RenLint(&'static str),
+}
+
+impl DiagnosticCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticCode::RenError(code) => code,
+            DiagnosticCode::RenLint(code) => code,
+        }
+    }
+}
+
+impl Display for DiagnosticCode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// Represents a message to report to the user as an output of compilation.
 #[derive(Clone, Debug)]
 pub struct Diagnostic {
     pub location: FileRange,
     pub message: String,
-    pub level: DiagnosticLevel
+    pub level: DiagnosticLevel,
+    pub suggestions: Vec<Suggestion>,
+    /// A "did you mean" name, proposed by edit-distance against the candidates that were
+    /// actually in scope where this diagnostic was raised. Distinct from `suggestions`: this
+    /// is a plain text hint for a name that doesn't exist at all, not a machine-applicable
+    /// edit to the source.
+    pub suggestion: Option<String>,
+    /// This diagnostic's stable code, if the pass that raised it has one registered yet. `None`
+    /// for diagnostics a pass hasn't been updated to attach a code to.
+    pub code: Option<DiagnosticCode>
 }
 
 impl Diagnostic {
@@ -119,7 +181,10 @@ impl Diagnostic {
         Diagnostic {
             message,
             location,
-            level: DiagnosticLevel::Error
+            level: DiagnosticLevel::Error,
+            suggestions: vec![],
+            suggestion: None,
+            code: None
         }
     }
 
@@ -130,7 +195,10 @@ impl Diagnostic {
         Diagnostic {
             message,
             location: FileRange::new(location.path, location.position, location.position),
-            level: DiagnosticLevel::Error
+            level: DiagnosticLevel::Error,
+            suggestions: vec![],
+            suggestion: None,
+            code: None
         }
     }
 
@@ -138,12 +206,80 @@ impl Diagnostic {
         self.level = level;
         self
     }
+
+    /// Attach a machine-applicable (or not) fix suggestion to this diagnostic.
+    pub fn with_suggestion(mut self, span: FileRange, replacement: String, applicability: Applicability) -> Diagnostic {
+        self.suggestions.push(Suggestion::new(span, replacement, applicability));
+        self
+    }
+
+    /// Attach a "did you mean `name`?" hint, proposed by edit distance against whatever was
+    /// actually in scope. No-op if `name` is `None`, so callers can thread an `Option` straight
+    /// through without an extra branch at the call site.
+    pub fn with_did_you_mean(mut self, name: Option<String>) -> Diagnostic {
+        self.suggestion = name;
+        self
+    }
+
+    /// Attach this diagnostic's stable code, the vocabulary `renlang explain` looks up by name.
+    pub fn with_code(mut self, code: DiagnosticCode) -> Diagnostic {
+        self.code = Some(code);
+        self
+    }
+
+    /// Splice this diagnostic's suggestions into `source`, replacing each suggestion's
+    /// span with its replacement text. Suggestions are applied in reverse span order so
+    /// that earlier offsets remain valid as later ones are spliced in.
+    pub fn apply_suggestions(&self, source: &str) -> String {
+        Diagnostic::apply_all(&[self.clone()], source)
+    }
+
+    /// Like `apply_suggestions`, but splices every suggestion across several diagnostics into
+    /// `source` in one pass, rather than one diagnostic at a time. Every suggestion's span is
+    /// converted to a byte offset against the original `source` up front, before any splicing
+    /// happens, and all of them are applied in a single reverse-sorted pass - so applying one
+    /// diagnostic's suggestions doesn't shift the line/column offsets the next diagnostic's
+    /// spans were computed against, the way re-running `apply_suggestions` against its own
+    /// progressively-mutated output would.
+    pub fn apply_all(diagnostics: &[Diagnostic], source: &str) -> String {
+        let mut spans: Vec<&Suggestion> = diagnostics.iter().flat_map(|d| d.suggestions.iter()).collect();
+        spans.sort_by_key(|s| s.span.start());
+        let mut result = source.to_string();
+        for suggestion in spans.iter().rev() {
+            let start = line_col_to_offset(source, suggestion.span.start());
+            let end = line_col_to_offset(source, suggestion.span.end());
+            result.replace_range(start..end, &suggestion.replacement);
+        }
+        result
+    }
+}
+
+/// Converts a (line, column) position (both 0-indexed) into a byte offset into `source`.
+fn line_col_to_offset(source: &str, (line, column): (usize, usize)) -> usize {
+    let mut offset = 0;
+    for (i, l) in source.split('\n').enumerate() {
+        if i == line { return offset + column; }
+        offset += l.len() + 1;
+    }
+    offset
 }
 
 impl Display for Diagnostic {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let FileRange { path, start: (line, column), .. } = self.location;
-        write!(f, "{:?}: {} ({}:{}:{})", self.level, self.message, path.to_str().ok_or(fmt::Error)?, line, column)
+        write!(f, "{:?}", self.level)?;
+        if let Some(code) = &self.code {
+            write!(f, "[{}]", code)?;
+        }
+        write!(f, ": {} ({}:{}:{})", self.message, path.to_str().ok_or(fmt::Error)?, line, column)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " - did you mean `{}`?", suggestion)?;
+        }
+        for suggestion in &self.suggestions {
+            let (s_line, s_col) = suggestion.span.start();
+            write!(f, "\n  suggestion ({:?}) at {}:{}: replace with `{}`", suggestion.applicability, s_line, s_col, suggestion.replacement)?;
+        }
+        Ok(())
     }
 }
 
@@ -158,12 +294,18 @@ impl Display for Diagnostic {
 /// 
 /// This type implements `Try` so it can be used with the `?` operator.
 /// This will yield a `Result<DiagResult, Vec<Diagnostic>>`.
-pub struct DiagResult<T>(Option<T>, Vec<Diagnostic>);
+pub struct DiagResult<T>(pub Option<T>, pub Vec<Diagnostic>);
 
 impl<T> DiagResult<T> {
     pub fn ok(result: T) -> DiagResult<T> {
         DiagResult(Some(result), vec![])
     }
+
+    /// Like `ok()`, but for a result that still carries diagnostics - e.g. a parse that
+    /// succeeded overall but recovered from one or more malformed constructs along the way.
+    pub fn ok_with_diagnostics(result: T, diagnostics: Vec<Diagnostic>) -> DiagResult<T> {
+        DiagResult(Some(result), diagnostics)
+    }
 }
 
 impl<T> Try for DiagResult<T> {
@@ -0,0 +1,87 @@
+use crate::core::DiagnosticCode;
+
+/// A code's full write-up: the rule it enforces, in plain language, plus a minimal snippet that
+/// reproduces it - the same shape rustc's `--explain` pages take, so `renlang explain REN0123`
+/// has something substantial to print.
+pub struct Explanation {
+    pub code: DiagnosticCode,
+    pub summary: &'static str,
+    pub explanation: &'static str,
+    pub example: &'static str,
+}
+
+/// Every code a pass in `analyze` is known to attach to a `Diagnostic`. A pass that hasn't been
+/// wired up to attach a code yet (typechecking and name-clash checking, both still unwritten -
+/// see `semantic::mod`) simply has no entries here until it is.
+const REGISTRY: &[Explanation] = &[
+    Explanation {
+        code: DiagnosticCode::RenError("REN0001"),
+        summary: "the entry point module failed to parse",
+        explanation: "The file passed to `renlang` as its entry point could not be parsed at \
+            all. Every other diagnostic from this pass is suppressed once this fires, since \
+            there is no module to enumerate anything from.",
+        example: "renlang ./main.ren   # main.ren contains unparseable syntax",
+    },
+    Explanation {
+        code: DiagnosticCode::RenError("REN0002"),
+        summary: "a referenced module could not be resolved",
+        explanation: "An `import`, `export ... from`, or `import from ... : *` named a module \
+            path that doesn't exist, or that failed to parse. The reference is still recorded \
+            so every site that named it gets its own diagnostic, rather than reporting the \
+            failure once at an arbitrary site.",
+        example: "import from \"does_not_exist\" : foo",
+    },
+    Explanation {
+        code: DiagnosticCode::RenError("REN0003"),
+        summary: "a wildcard re-export participates in a cycle",
+        explanation: "Two or more modules forward everything from each other with \
+            `export * from \"...\"`, so there is no well-defined order in which to expand what \
+            either one actually exports.",
+        example: "// a.ren\nexport * from \"b\"\n// b.ren\nexport * from \"a\"",
+    },
+    Explanation {
+        code: DiagnosticCode::RenError("REN0004"),
+        summary: "a submodule's instantiation graph contains a cycle",
+        explanation: "A `submodule A = F X` instantiation depends, transitively, on its own \
+            submodule - there is no order in which the submodules of this tree could be \
+            resolved.",
+        example: "submodule A = F B\nsubmodule B = F A",
+    },
+    Explanation {
+        code: DiagnosticCode::RenError("REN0005"),
+        summary: "a name is not declared in the scope it was used",
+        explanation: "An `export foo` (or another reference to a local name) named something \
+            that isn't actually declared anywhere in that namespace. If a similarly-spelled \
+            name is in scope, the diagnostic also proposes it as a \"did you mean\" suggestion.",
+        example: "export foo\n// no local declaration named `foo` anywhere in this module",
+    },
+    Explanation {
+        code: DiagnosticCode::RenError("REN0006"),
+        summary: "an imported or forwarded export does not exist",
+        explanation: "An `import`, `export ... from`, or glob-expanded forward named an export \
+            that the target module doesn't actually have. If a similarly-spelled export does \
+            exist, the diagnostic also proposes it as a \"did you mean\" suggestion.",
+        example: "import from \"mod\" : doesNotExist",
+    },
+    Explanation {
+        code: DiagnosticCode::RenError("REN0007"),
+        summary: "a name is part of a circular reference chain",
+        explanation: "Resolving this name requires resolving another name that, transitively, \
+            depends on this one again - unlike a wildcard re-export cycle (REN0003), this is a \
+            cycle through ordinary named imports, forwards, or exports.",
+        example: "export foo\n// where `foo` ultimately re-exports itself through some chain \
+            of other names",
+    },
+];
+
+/// Looks up the full explanation for a code by its short name (e.g. `"REN0005"`), the way
+/// `renlang explain` is invoked from the command line.
+pub fn lookup(code: &str) -> Option<&'static Explanation> {
+    REGISTRY.iter().find(|entry| entry.code.as_str() == code)
+}
+
+/// Renders an explanation the way `renlang explain` prints it: the code and summary, the full
+/// prose explanation, and a minimal reproducing example.
+pub fn render(entry: &Explanation) -> String {
+    format!("{} - {}\n\n{}\n\nExample:\n\n{}\n", entry.code, entry.summary, entry.explanation, entry.example)
+}
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::core::{Applicability, Diagnostic};
+
+/// Groups every `MachineApplicable` suggestion across `diagnostics` by the file it edits, and
+/// rewrites each affected file once with all of that file's suggestions spliced in -
+/// `Diagnostic::apply_suggestions` already does the splice for one diagnostic at a time, so this
+/// just applies it once per file instead of once per diagnostic, so two suggestions landing in
+/// the same file don't each read-modify-write it out from under the other. Only
+/// `MachineApplicable` suggestions are applied: anything less certain is left for
+/// `render_diagnostic`'s "help:" text to surface instead, the same distinction `rustc --fix`
+/// draws against its own `Applicability`.
+///
+/// Returns every file actually rewritten, so the caller can report what `--fix` touched before
+/// re-running `analyze` to confirm the fixes took.
+pub fn apply_fixes(diagnostics: &[Diagnostic]) -> Vec<PathBuf> {
+    let mut by_file: HashMap<PathBuf, Vec<&Diagnostic>> = HashMap::new();
+    for diagnostic in diagnostics {
+        let has_machine_applicable = diagnostic.suggestions.iter()
+            .any(|s| s.applicability == Applicability::MachineApplicable);
+        if has_machine_applicable {
+            by_file.entry(diagnostic.location.path().to_path_buf()).or_insert_with(Vec::new).push(diagnostic);
+        }
+    }
+
+    let mut touched = vec![];
+    for (path, diags) in by_file {
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(_) => continue,
+        };
+        // Every suggestion's span is converted to a byte offset against this original `source`
+        // up front and spliced in as a single reverse-sorted pass (via `Diagnostic::apply_all`),
+        // rather than applying each diagnostic's suggestions one at a time into a progressively-
+        // mutated string - which would corrupt later splices once an earlier one changes the
+        // text those spans were computed against.
+        let machine_applicable_only: Vec<Diagnostic> = diags.into_iter()
+            .map(|diagnostic| Diagnostic {
+                suggestions: diagnostic.suggestions.iter()
+                    .filter(|s| s.applicability == Applicability::MachineApplicable)
+                    .cloned()
+                    .collect(),
+                ..diagnostic.clone()
+            })
+            .collect();
+        let fixed = Diagnostic::apply_all(&machine_applicable_only, &source);
+        if fs::write(&path, &fixed).is_ok() {
+            touched.push(path);
+        }
+    }
+    touched
+}
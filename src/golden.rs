@@ -0,0 +1,247 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::core::{Diagnostic, DiagnosticLevel};
+use crate::semantic::analyze;
+
+/// Marks the line of a `.ren` fixture a diagnostic is expected on, e.g.
+/// `//~ ERROR REN0006 has no export`. `code` is optional - a fixture asserting only that
+/// *some* error fires on a line, without pinning a code yet, just omits it - and `substring` only
+/// has to appear somewhere in the diagnostic's message, not match it exactly, so a fixture stays
+/// green across message-wording tweaks as long as the code (or substring) still identifies it.
+struct Expectation {
+    line: usize,
+    level: DiagnosticLevel,
+    code: Option<String>,
+    substring: String,
+}
+
+/// One fixture's result: every expectation that no emitted diagnostic satisfied, and every
+/// diagnostic that didn't satisfy any expectation. A fixture passes when both are empty.
+pub struct GoldenResult {
+    pub path: PathBuf,
+    pub unmatched_expectations: Vec<String>,
+    pub unexpected_diagnostics: Vec<String>,
+}
+
+impl GoldenResult {
+    pub fn passed(&self) -> bool {
+        self.unmatched_expectations.is_empty() && self.unexpected_diagnostics.is_empty()
+    }
+}
+
+/// Runs `analyze` over the fixture at `path` and checks its diagnostics against the `//~`
+/// annotations written inline in the fixture's own source.
+pub fn run_fixture(path: PathBuf, trace_parse: bool) -> io::Result<GoldenResult> {
+    let source = fs::read_to_string(&path)?;
+    let mut expectations = parse_expectations(&source);
+
+    let crate::core::DiagResult(_, diagnostics) = analyze(path.clone(), trace_parse);
+
+    let mut unexpected_diagnostics = vec![];
+    for diagnostic in &diagnostics {
+        let (line, _) = diagnostic.location.start();
+        match expectations.iter().position(|e| matches(e, diagnostic, line)) {
+            Some(index) => { expectations.remove(index); }
+            None => unexpected_diagnostics.push(describe_diagnostic(diagnostic, line)),
+        }
+    }
+    let unmatched_expectations = expectations.iter().map(describe_expectation).collect();
+
+    Ok(GoldenResult { path, unmatched_expectations, unexpected_diagnostics })
+}
+
+/// Rewrites every `//~` annotation in the fixture at `path` to match `analyze`'s current
+/// output, the way a snapshot test suite's "bless" mode updates its golden files instead of
+/// making the developer hand-edit every line that changed on purpose.
+pub fn bless_fixture(path: PathBuf, trace_parse: bool) -> io::Result<()> {
+    let source = fs::read_to_string(&path)?;
+    let crate::core::DiagResult(_, diagnostics) = analyze(path.clone(), trace_parse);
+
+    let mut by_line: std::collections::HashMap<usize, Vec<&Diagnostic>> = std::collections::HashMap::new();
+    for diagnostic in &diagnostics {
+        let (line, _) = diagnostic.location.start();
+        by_line.entry(line).or_insert_with(Vec::new).push(diagnostic);
+    }
+
+    let blessed: Vec<String> = source.lines().enumerate().map(|(line, text)| {
+        let code_text = strip_annotation(text);
+        match by_line.get(&line) {
+            Some(diags) => {
+                let annotations: Vec<String> = diags.iter().map(|d| annotation_for(d)).collect();
+                format!("{} {}", code_text, annotations.join(" "))
+            }
+            None => code_text.to_string(),
+        }
+    }).collect();
+
+    fs::write(path, blessed.join("\n") + "\n")
+}
+
+/// Parses every `//~ LEVEL [CODE] substring...` annotation out of `source`, one per line it
+/// appears on - a line with no `//~` comment has no expectation at all.
+fn parse_expectations(source: &str) -> Vec<Expectation> {
+    source.lines().enumerate().filter_map(|(line, text)| {
+        let marker = text.find("//~")?;
+        let rest = text[marker + 3..].trim();
+        let mut parts = rest.splitn(3, ' ');
+        let level = parse_level(parts.next()?)?;
+        let remainder = parts.next()?;
+        let (code, substring) = match parts.next() {
+            Some(substring) if is_code(remainder) => (Some(remainder.to_owned()), substring.to_owned()),
+            Some(substring) => (None, format!("{} {}", remainder, substring)),
+            None => (None, remainder.to_owned()),
+        };
+        Some(Expectation { line, level, code, substring })
+    }).collect()
+}
+
+fn parse_level(text: &str) -> Option<DiagnosticLevel> {
+    match text {
+        "FATAL" => Some(DiagnosticLevel::Fatal),
+        "ERROR" => Some(DiagnosticLevel::Error),
+        "WARNING" => Some(DiagnosticLevel::Warning),
+        "MESSAGE" => Some(DiagnosticLevel::Message),
+        "VERBOSE" => Some(DiagnosticLevel::Verbose),
+        _ => None,
+    }
+}
+
+/// A code looks like `REN0123` - this repo's only convention for one (see `DiagnosticCode`) -
+/// distinguishing it from the first word of a substring that happens to come right after the
+/// level.
+fn is_code(text: &str) -> bool {
+    text.starts_with("REN") && text[3..].chars().all(|c| c.is_ascii_digit()) && text.len() > 3
+}
+
+fn matches(expectation: &Expectation, diagnostic: &Diagnostic, line: usize) -> bool {
+    expectation.line == line
+        && expectation.level == diagnostic.level
+        && expectation.code.as_deref().map_or(true, |code| diagnostic.code.map_or(false, |c| c.as_str() == code))
+        && diagnostic.message.contains(&expectation.substring)
+}
+
+fn describe_expectation(expectation: &Expectation) -> String {
+    let code = expectation.code.as_deref().map(|c| format!("{} ", c)).unwrap_or_default();
+    format!("line {}: expected {:?} {}\"{}\"", expectation.line + 1, expectation.level, code, expectation.substring)
+}
+
+fn describe_diagnostic(diagnostic: &Diagnostic, line: usize) -> String {
+    let code = diagnostic.code.map(|c| format!("{} ", c)).unwrap_or_default();
+    format!("line {}: unexpected {:?} {}\"{}\"", line + 1, diagnostic.level, code, diagnostic.message)
+}
+
+fn annotation_for(diagnostic: &Diagnostic) -> String {
+    let level = match diagnostic.level {
+        DiagnosticLevel::Fatal => "FATAL",
+        DiagnosticLevel::Error => "ERROR",
+        DiagnosticLevel::Warning => "WARNING",
+        DiagnosticLevel::Message => "MESSAGE",
+        DiagnosticLevel::Verbose => "VERBOSE",
+    };
+    match &diagnostic.code {
+        Some(code) => format!("//~ {} {} {}", level, code, diagnostic.message),
+        None => format!("//~ {} {}", level, diagnostic.message),
+    }
+}
+
+/// Drops an existing `//~` annotation (and any trailing whitespace before it) off of `line`,
+/// so `bless_fixture` replaces a stale annotation rather than appending another one after it.
+fn strip_annotation(line: &str) -> &str {
+    match line.find("//~") {
+        Some(marker) => line[..marker].trim_end(),
+        None => line,
+    }
+}
+
+/// Runs every `.ren` fixture directly inside `dir` (non-recursively, matching how fixtures for
+/// this harness are expected to be laid out - one flat directory per suite) and returns one
+/// `GoldenResult` per file, in directory iteration order.
+///
+/// `fixtures/golden/` carries one suite per resolution diagnostic this harness can currently
+/// name a code for (REN0002-REN0006). They can't be run end to end yet: `ModuleRoot`'s own
+/// `parse_func` composes against declaration types (`ImportDeclaration`, `NonImport`, ...) that
+/// aren't real parsers yet, so nothing can actually reach the resolution pass these fixtures
+/// are meant to exercise until that grammar is written. They're checked in now as the target
+/// shape for that point, annotated exactly as `run_fixture` expects.
+pub fn run_suite(dir: &Path, trace_parse: bool) -> io::Result<Vec<GoldenResult>> {
+    let mut results = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().map_or(false, |ext| ext == "ren") {
+            results.push(run_fixture(path, trace_parse)?);
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `parse_expectations`, `matches`, and the rest of the annotation machinery below don't
+    /// depend on `analyze` or `ModuleRoot`'s grammar at all - they're plain text/`Diagnostic`
+    /// munging - so unlike `golden_suites_pass` they run for real under `cargo test` today.
+    /// Keeping them covered here means the harness isn't verified *only* by the one test that's
+    /// blocked on declaration parsing.
+    #[test]
+    fn parse_expectations_reads_level_code_and_substring() {
+        let source = "let x = 1 //~ ERROR REN0002 duplicate export\nlet y = 2 //~ WARNING unused binding\n";
+        let expectations = parse_expectations(source);
+        assert_eq!(expectations.len(), 2);
+        assert_eq!(expectations[0].line, 0);
+        assert_eq!(expectations[0].level, DiagnosticLevel::Error);
+        assert_eq!(expectations[0].code.as_deref(), Some("REN0002"));
+        assert_eq!(expectations[0].substring, "duplicate export");
+        assert_eq!(expectations[1].line, 1);
+        assert_eq!(expectations[1].code, None);
+        assert_eq!(expectations[1].substring, "unused binding");
+    }
+
+    #[test]
+    fn is_code_requires_ren_prefix_and_digits() {
+        assert!(is_code("REN0007"));
+        assert!(!is_code("REN"));
+        assert!(!is_code("RENabcd"));
+        assert!(!is_code("duplicate"));
+    }
+
+    #[test]
+    fn strip_annotation_drops_trailing_comment_only() {
+        assert_eq!(strip_annotation("let x = 1 //~ ERROR whatever"), "let x = 1");
+        assert_eq!(strip_annotation("let x = 1"), "let x = 1");
+    }
+
+    /// Runs every suite under `fixtures/golden/` through `run_suite`, one subdirectory at a
+    /// time, and asserts each fixture matched its own `//~` annotations - the same harness the
+    /// `golden` CLI subcommand drives by hand, now wired into `cargo test` instead of only
+    /// being reachable that way.
+    ///
+    /// Still ignored until `ModuleRoot`'s grammar actually parses declarations - see `run_suite`'s
+    /// doc comment above. `non_term::<ImportDeclaration>()` doesn't resolve to a real parser
+    /// yet, so there's no program for `analyze` to produce diagnostics for; this is left in
+    /// place, ignored, as the shape the suite should run under once that grammar exists. The
+    /// unit tests above this one are what actually run under `cargo test` today - this one alone
+    /// was never sufficient to back the claim that the harness has real, executing coverage.
+    #[test]
+    #[ignore = "blocked on declaration grammar - see run_suite's doc comment"]
+    fn golden_suites_pass() {
+        let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/golden");
+        for entry in fs::read_dir(&fixtures_dir).expect("fixtures/golden should exist") {
+            let suite_dir = entry.expect("readable fixtures/golden entry").path();
+            if !suite_dir.is_dir() {
+                continue;
+            }
+            for result in run_suite(&suite_dir, false).expect("run_suite should read its dir") {
+                assert!(
+                    result.passed(),
+                    "{}: unmatched={:?} unexpected={:?}",
+                    result.path.display(),
+                    result.unmatched_expectations,
+                    result.unexpected_diagnostics
+                );
+            }
+        }
+    }
+}
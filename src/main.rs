@@ -3,26 +3,108 @@
 #![feature(try_trait)]
 
 use std::env;
+use std::fs;
 use std::io;
 use std::process;
 
 mod core;
+mod explain;
+mod fix;
+mod golden;
+mod message_format;
 mod parser;
+mod render;
 mod runner;
 mod semantic;
 mod syntax;
 mod utils;
 
+use message_format::MessageFormat;
+use render::ColorMode;
+
 fn main() -> io::Result<()> {
     // extract the program path and arguments
-    let args: Vec<String> = env::args().collect();
-    let path = &args[1];
-    let args = &args[2..];
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    // opt-in grammar debugging: prints an indented enter/exit trace line for every parse
+    // rule attempted, naming the rule and whether it succeeded
+    let trace_parse = match args.iter().position(|a| a == "--trace-parse") {
+        Some(pos) => { args.remove(pos); true }
+        None => false,
+    };
+    // controls whether rendered diagnostics are colorized; defaults to `auto` when unspecified
+    let color = match args.iter().position(|a| a.starts_with("--color=")) {
+        Some(pos) => {
+            let flag = args.remove(pos);
+            ColorMode::parse(&flag["--color=".len()..]).unwrap_or(ColorMode::Auto)
+        }
+        None => ColorMode::Auto,
+    };
+    // controls whether diagnostics print as human-readable snippets or one JSON object per line
+    let message_format = match args.iter().position(|a| a.starts_with("--message-format=")) {
+        Some(pos) => {
+            let flag = args.remove(pos);
+            MessageFormat::parse(&flag["--message-format=".len()..]).unwrap_or(MessageFormat::Human)
+        }
+        None => MessageFormat::Human,
+    };
+    // rewrites every machine-applicable suggestion in place, then re-analyzes to confirm the
+    // fix actually resolved the diagnostic that proposed it
+    let apply_fixes = match args.iter().position(|a| a == "--fix") {
+        Some(pos) => { args.remove(pos); true }
+        None => false,
+    };
+    // `renlang repl` keeps a session open across lines instead of running a single file once
+    if args[0] == "repl" {
+        process::exit(runner::repl(trace_parse));
+    }
+    // `renlang explain REN0123` prints a code's full write-up and exits without analyzing
+    // anything - it doesn't even need a path
+    if args[0] == "explain" {
+        let code = args.get(1).map(String::as_str).unwrap_or("");
+        return match explain::lookup(code) {
+            Some(entry) => { println!("{}", explain::render(entry)); process::exit(0); }
+            None => { eprintln!("No explanation registered for code \"{}\"", code); process::exit(1); }
+        };
+    }
+    // `renlang golden <dir> [--bless]` runs every `.ren` fixture in `dir` against its inline
+    // `//~` expectations, or - under `--bless` - rewrites those expectations from the fixtures'
+    // current diagnostics instead of checking them
+    if args[0] == "golden" {
+        let bless = args.iter().any(|a| a == "--bless");
+        let dir = env::current_dir()?.join(args.get(1).map(String::as_str).unwrap_or("."));
+        if bless {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.extension().map_or(false, |ext| ext == "ren") {
+                    golden::bless_fixture(path, trace_parse)?;
+                }
+            }
+            process::exit(0);
+        }
+        let results = golden::run_suite(&dir, trace_parse)?;
+        let mut failed = 0;
+        for result in &results {
+            if result.passed() { continue; }
+            failed += 1;
+            eprintln!("FAIL {}", result.path.display());
+            for unmatched in &result.unmatched_expectations { eprintln!("  {}", unmatched); }
+            for unexpected in &result.unexpected_diagnostics { eprintln!("  {}", unexpected); }
+        }
+        eprintln!("{} passed, {} failed", results.len() - failed, failed);
+        process::exit(if failed > 0 { 1 } else { 0 });
+    }
+
+    let path = &args[0];
+    let args = &args[1..];
 
     // run the program
     let exitCode = runner::run_program(
         env::current_dir()?.join(path),
-        args
+        args,
+        trace_parse,
+        color,
+        message_format,
+        apply_fixes
     );
 
     // exit the process
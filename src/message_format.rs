@@ -0,0 +1,67 @@
+use crate::core::Diagnostic;
+
+/// Selects how `run_program` prints diagnostics, set by `--message-format=human|json`. Human is
+/// the default - the carets-and-color rendering in `render` - while `json` emits one
+/// machine-readable object per diagnostic for editors and CI to consume.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MessageFormat {
+    Human,
+    Json,
+}
+
+impl MessageFormat {
+    pub fn parse(value: &str) -> Option<MessageFormat> {
+        match value {
+            "human" => Some(MessageFormat::Human),
+            "json" => Some(MessageFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Orders diagnostics by file path, then by span start, then by span end - the stable ordering
+/// `--message-format=json` prints in, so two runs over the same input produce byte-identical
+/// output and a golden-file comparison doesn't flap on incidental pass-ordering differences.
+pub fn sort_for_output(diagnostics: &mut Vec<&Diagnostic>) {
+    diagnostics.sort_by(|a, b| {
+        let a_path = a.location.path();
+        let b_path = b.location.path();
+        a_path.cmp(b_path)
+            .then(a.location.start().cmp(&b.location.start()))
+            .then(a.location.end().cmp(&b.location.end()))
+    });
+}
+
+/// Serializes one `Diagnostic` as a single-line JSON object: level, code, message, file path,
+/// its span as line/column start+end (0-indexed, same as `FileRange` itself stores them), and
+/// its "did you mean" suggestion.
+/// No JSON library is vendored in this tree, so this builds the object by hand rather than
+/// pulling one in - every field here is either a plain string, a number, or `null`, so there's
+/// no need for anything more general.
+pub fn render_diagnostic_json(diagnostic: &Diagnostic) -> String {
+    let (start_line, start_col) = diagnostic.location.start();
+    let (end_line, end_col) = diagnostic.location.end();
+    let code = match &diagnostic.code {
+        Some(code) => format!("\"{}\"", json_escape(code.as_str())),
+        None => "null".to_owned(),
+    };
+    let suggestion = match &diagnostic.suggestion {
+        Some(suggestion) => format!("\"{}\"", json_escape(suggestion)),
+        None => "null".to_owned(),
+    };
+
+    format!(
+        "{{\"level\":\"{:?}\",\"code\":{},\"message\":\"{}\",\"file\":\"{}\",\"span\":{{\"start\":{{\"line\":{},\"column\":{}}},\"end\":{{\"line\":{},\"column\":{}}}}},\"suggestion\":{}}}",
+        diagnostic.level,
+        code,
+        json_escape(&diagnostic.message),
+        json_escape(&diagnostic.location.path().to_string_lossy()),
+        start_line, start_col,
+        end_line, end_col,
+        suggestion
+    )
+}
+
+fn json_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,54 @@
+/// A forward-only cursor over source text that scans by byte offset instead of decoding the
+/// whole file into a `Vec<char>` up front. `char_at` only pays for real UTF-8 decoding on a
+/// byte `>= 0x80`; the common ASCII case is a single array index.
+pub struct Cursor<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(source: &'a str) -> Cursor<'a> {
+        Cursor { source, bytes: source.as_bytes(), pos: 0 }
+    }
+
+    /// Decodes the character starting at byte offset `at`, or `None` past the end of the source.
+    fn char_at(&self, at: usize) -> Option<char> {
+        let &b = self.bytes.get(at)?;
+        if b < 0x80 {
+            Some(b as char)
+        } else {
+            self.source[at..].chars().next()
+        }
+    }
+
+    /// Returns the character at the front of the cursor without consuming it.
+    pub fn peek(&self) -> Option<char> {
+        self.char_at(self.pos)
+    }
+
+    /// Returns the next `count` characters without consuming any of them, padded with `None`
+    /// past the end of the source so the result always has exactly `count` entries.
+    pub fn peeks(&self, count: usize) -> Vec<Option<char>> {
+        let mut result = Vec::with_capacity(count);
+        let mut at = self.pos;
+        for _ in 0..count {
+            match self.char_at(at) {
+                Some(c) => {
+                    result.push(Some(c));
+                    at += c.len_utf8();
+                }
+                None => result.push(None)
+            }
+        }
+        result
+    }
+
+    /// Consumes and returns the character at the front of the cursor, or `None` if the source
+    /// is exhausted. This is the only place `pos` moves forward.
+    pub fn next(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+}
@@ -1,11 +1,12 @@
 use std::fs;
-use std::io::{self, Read};
-use std::{path::PathBuf, str::Chars};
+use std::io;
+use std::path::PathBuf;
 use crate::core::{Diagnostic, FilePosition};
-use crate::utils::backtrack_iter::{IteratorExt, BacktrackIterator};
+use cursor::Cursor;
 
-pub use token::{TokenType, Token};
+pub use token::{TokenType, Token, TokenValue, Trivia};
 
+mod cursor;
 mod token;
 
 // #region Token/character sets
@@ -91,15 +92,33 @@ const IGNORED_TYPES: [TokenType; 2] = [TokenType::Comment, TokenType::Whitespace
 // #region Token stream logic
 
 /// An iterator for a `Token` stream.
-/// 
+///
 /// Each yielded item will be one of the following:
-/// * a `Some(Ok(Token))` if a `Token` could be validly consumed
-/// * a `Some(Err(Diagnostic))` if a `Token` could not be consumed
-/// * a `None` if all characters have been consumed or there was previously an error
+/// * a `Some(Ok(Token))` if a `Token` could be validly consumed - this includes a
+///   `TokenType::Error` token for a span the lexer couldn't otherwise classify, which carries
+///   its own `Diagnostic` (see `Token::error`) so the stream can keep going instead of aborting
+/// * a `Some(Err(Diagnostic))`, reserved for failures that leave no sensible token to recover
+///   with at all
+/// * a `None` once all characters have been consumed (the terminal `Eof` token was yielded)
 pub struct Tokens<'a> {
     position: FilePosition,
     terminated: bool,
-    chars: BacktrackIterator<Chars<'a>>,
+    chars: Cursor<'a>,
+    /// `true` while collecting the text of a string literal, as opposed to lexing normal tokens
+    /// within a `${...}` interpolation embedded in one.
+    is_within_text: bool,
+    /// One entry per currently-open `${...}` interpolation, counting the extra `{`/`}` nesting
+    /// (struct literals, lambda bodies) seen since entering it. A `}` only closes the
+    /// interpolation back to text mode when it pops a frame sitting at count `0`; any other `}`
+    /// just decrements its frame, and a nested `{` increments it.
+    brace_depth: Vec<u32>,
+    /// Off by default: bidirectional-control and other deceptive/invisible codepoints inside
+    /// string literals are rejected with a diagnostic rather than silently accepted, since they
+    /// enable source-spoofing ("Trojan Source") attacks. Set via `with_confusing_unicode_guard`.
+    /// Only string literal text is checked: identifiers take ASCII letters, digits and `_` only,
+    /// so such a codepoint is an invalid character there whatever this is set to, and char
+    /// literals are not lexed.
+    allow_confusing_unicode: bool,
 }
 
 impl<'a> Iterator for Tokens<'a> {
@@ -123,22 +142,48 @@ impl Tokens<'_> {
 
     /// Same as `from_file_path`, but does not ignore whitespace and comments.
     pub fn from_file_path_no_ignore<'a>(path: PathBuf) -> io::Result<Tokens<'a>> {
-        let file = fs::File::open(path)?;
-        let string = String::new();
-        file.read_to_string(&mut string)?;
-        Ok(Tokens {
-            chars: string.chars().backtrack(),
+        let source = fs::read_to_string(&path)?;
+        Ok(Self::from_str(&source, path))
+    }
+
+    /// Lexes `source` directly - the reusable core underneath `from_file_path`. `path` is
+    /// attached to positions and diagnostics purely as a label (e.g. `"<repl>"`); it need not
+    /// name a real file. Editors, a REPL, and test harnesses can all lex an in-memory buffer
+    /// this way without ever touching the filesystem, and can re-lex it after every edit
+    /// without re-reading anything from disk.
+    pub fn from_str<'a>(source: &'a str, path: PathBuf) -> Tokens<'a> {
+        Tokens {
+            chars: Cursor::new(source),
             terminated: false,
-            position: FilePosition::new(path, (0, 0))
-        })
+            position: FilePosition::new(path, (0, 0)),
+            is_within_text: false,
+            brace_depth: vec![],
+            allow_confusing_unicode: false
+        }
+    }
+
+    /// Enables (or disables) acceptance of bidirectional-control and other deceptive/invisible
+    /// codepoints inside string literals (identifiers never accept them). Off by default, since these
+    /// codepoints exist mainly to make source text render as something other than what it is.
+    pub fn with_confusing_unicode_guard(mut self, allow: bool) -> Tokens<'a> {
+        self.allow_confusing_unicode = allow;
+        self
     }
 
     /// Consumes a single token from the front of the stream and returns it.
-    /// 
-    /// If there was a lexical error, that will be returned instead.
+    ///
+    /// An unrecognized character does not end the stream: it is consumed into a
+    /// `TokenType::Error` token carrying the offending text and a diagnostic, and lexing
+    /// resumes from the following character.
     fn consume_token(&self) -> Option<Result<Token, Diagnostic>> {
         if self.terminated { panic!("consume_token() should not be called after it yields an EOF") }
 
+        // mid-string, not between tokens of an embedded `${...}` expression: keep collecting
+        // the next fragment of text rather than dispatching on what starts a normal token
+        if self.is_within_text {
+            return Some(self.consume_string_fragment());
+        }
+
         match self.look2() {
             // eof()
             (None, _) => {
@@ -152,23 +197,39 @@ impl Tokens<'_> {
             (Some('/'), Some('*')) =>
                 self.consume_multi_line_comment(),
             // seq(<ident_start>, repeat(select(<ident>), OnePlus))
-            /*(Some(c), _) if is_ident(c) =>
+            (Some(c), _) if is_ident(c) =>
                 self.consume_identifier_or_reserved(),
-            // 
+            // consume either: hex, binary, float, decimal
             (Some(c), _) if kind(c) == CharKind::Num =>
                 self.consume_number(),
             // seq('"', repeat(not))
-            (Some(c), _) if c == '"' =>
-                self.consume_string_literal(),
-            //
-            (Some(c), _) if c == '\'' =>
-                self.consume_char_literal(),
-            //
+            (Some('"'), _) =>
+                Some(self.consume_string_literal()),
+            // a `}` that pops the innermost interpolation back to depth 0 ends it; resume
+            // collecting text for the next fragment instead of treating this as a plain symbol
+            (Some('}'), _) if self.closes_interpolation() =>
+                Some(self.consume_string_fragment()),
+            // braces nested inside an interpolated expression (struct literals, lambda bodies)
+            // just adjust the depth counter so the matching `}` is the one that ends it - these
+            // frames are tracked structurally for depth-counting rather than tokenized through
+            // the normal symbol path below, so they still fall through as errors here
+            (Some('{'), _) if !self.brace_depth.is_empty() => {
+                self.bump_interpolation_depth(1);
+                self.consume_invalid_char('{')
+            }
+            (Some('}'), _) if !self.brace_depth.is_empty() => {
+                self.bump_interpolation_depth(-1);
+                self.consume_invalid_char('}')
+            }
+            // seq('=', opt(ch('>'))) or any other RESERVED_SYMBOLS entry
             (Some(c), _) if is_reserved_sym(c) =>
                 self.consume_symbol(),
-            //
+            // repeat(select(OPER_CHARS), OnePlus), with '<'/'>' always kept single-character
             (Some(c), _) if is_oper(c) =>
                 self.consume_operator(),
+            /*//
+            (Some(c), _) if c == '\'' =>
+                self.consume_char_literal(),
             //
             (Some(c), c1) if is_newline(c, c1) =>
                 self.consume_newline(),
@@ -176,23 +237,191 @@ impl Tokens<'_> {
             (Some(c), _) if c == ' ' || c == '\t' =>
                 self.consume_whitespace(),*/
             //
-            (Some(c), _) => Some(Err(Diagnostic::new(
-                format!("Invalid character '{}'", c),
-                self.position.compute_range("")
-            )))
+            (Some(c), _) => self.consume_invalid_char(c)
         }
     }
 
+    /// Consumes a single character that doesn't start any recognized token, wrapping it in a
+    /// `TokenType::Error` token so the stream can resynchronize on the next character instead
+    /// of aborting.
+    fn consume_invalid_char(&self, c: char) -> Option<Result<Token, Diagnostic>> {
+        let token = Token::new(TokenType::Error, self.position.clone());
+        let diagnostic = Diagnostic::new(
+            format!("Invalid character '{}'", c),
+            self.position.compute_range("")
+        );
+        self.advance_token(&token, c);
+        token.set_error(diagnostic);
+        Some(Ok(token))
+    }
+
+    /// If the innermost open interpolation's brace count is at `0`, this `}` is the one that
+    /// ends it: pops its frame and switches back to text mode, and returns `true`. Otherwise
+    /// (no interpolation open, or its count is above `0`) returns `false` without popping.
+    fn closes_interpolation(&self) -> bool {
+        match self.brace_depth.last() {
+            Some(0) => {
+                self.brace_depth.pop();
+                self.is_within_text = true;
+                true
+            }
+            _ => false
+        }
+    }
+
+    /// Adjusts the innermost open interpolation's nested-brace count by `delta`.
+    /// Only called once `brace_depth` is already known to be non-empty.
+    fn bump_interpolation_depth(&self, delta: i32) {
+        let top = self.brace_depth.last_mut().expect("bump_interpolation_depth() requires an open interpolation");
+        *top = (*top as i32 + delta) as u32;
+    }
+
+    /// Consumes the opening `"` of a string literal and its first fragment of text.
+    /// If the fragment runs into an interpolation, the token becomes a `StringFragment`
+    /// instead; otherwise it stays a whole `StringLiteral`.
+    fn consume_string_literal(&self) -> Result<Token, Diagnostic> {
+        let token = Token::new(TokenType::StringLiteral, self.position.clone());
+        self.advance_token(&token, '"');
+        self.consume_string_fragment_body(token)
+    }
+
+    /// Resumes collecting a string's text right after an interpolation's closing `}`.
+    /// Unlike `consume_string_literal`, this always produces a `StringFragment`, since
+    /// there was necessarily an expression before it.
+    fn consume_string_fragment(&self) -> Result<Token, Diagnostic> {
+        let token = Token::new(TokenType::StringFragment, self.position.clone());
+        self.consume_string_fragment_body(token)
+    }
+
+    /// Literal string text: any character up to an unescaped `"` (ends the string) or an
+    /// unescaped `${` (opens an interpolation and hands control back to `consume_token`).
+    /// Recognizes the usual escapes, plus `\xHH` and `\u{H..H}` (1-6 hex digits). Rejects a
+    /// confusing/invisible codepoint appearing literally in the text unless the lexer was
+    /// built `with_confusing_unicode_guard(true)`.
+    fn consume_string_fragment_body(&self, token: Token) -> Result<Token, Diagnostic> {
+        let mut value = String::new();
+        loop {
+            match self.look2() {
+                (None, _) | (Some('\\'), None) => return Err(Diagnostic::new(
+                    "Unterminated string".to_owned(),
+                    token.range()
+                )),
+                (Some('"'), _) => {
+                    self.advance_token(&token, '"');
+                    token.set_value(TokenValue::String(value));
+                    return Ok(token);
+                }
+                (Some('$'), Some('{')) => {
+                    self.advance_token(&token, '$');
+                    self.advance_token(&token, '{');
+                    token.set_token_type(TokenType::StringFragment);
+                    token.set_value(TokenValue::String(value));
+                    self.brace_depth.push(0);
+                    self.is_within_text = false;
+                    return Ok(token);
+                }
+                (Some('\\'), Some('x')) => {
+                    self.advance_token(&token, '\\');
+                    self.advance_token(&token, 'x');
+                    let code = self.consume_hex_escape(&token, 2)?;
+                    value.push(char::from_u32(code).ok_or_else(|| Diagnostic::new(
+                        "Invalid character escape".to_owned(),
+                        token.range()
+                    ))?);
+                }
+                (Some('\\'), Some('u')) => {
+                    self.advance_token(&token, '\\');
+                    self.advance_token(&token, 'u');
+                    value.push(self.consume_unicode_escape(&token)?);
+                }
+                (Some('\\'), Some(e)) => {
+                    self.advance_token(&token, '\\');
+                    self.advance_token(&token, e);
+                    value.push(match e {
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'f' => '\u{0C}',
+                        'b' => '\u{08}',
+                        'v' => '\u{0B}',
+                        '0' => '\0',
+                        other => other
+                    });
+                }
+                (Some(c), _) if is_confusing_unicode(c) && !self.allow_confusing_unicode => {
+                    return Err(Diagnostic::new(
+                        format!(
+                            "Confusing or invisible character U+{:04X} in string literal (pass `allow_confusing_unicode` to permit it)",
+                            c as u32
+                        ),
+                        token.range()
+                    ));
+                }
+                (Some(c), _) => {
+                    self.advance_token(&token, c);
+                    value.push(c);
+                }
+            }
+        }
+    }
+
+    /// Consumes exactly `count` hex digits (as in `\xHH`) and returns their value.
+    fn consume_hex_escape(&self, token: &Token, count: usize) -> Result<u32, Diagnostic> {
+        let mut value = 0u32;
+        for _ in 0..count {
+            match self.chars.peek() {
+                Some(c) if c.is_ascii_hexdigit() => {
+                    self.advance_token(token, c);
+                    value = value * 16 + c.to_digit(16).unwrap();
+                }
+                _ => return Err(Diagnostic::new(
+                    "Invalid character escape".to_owned(),
+                    token.range()
+                ))
+            }
+        }
+        Ok(value)
+    }
+
+    /// Consumes a brace-delimited unicode escape (`\u{H..H}`, 1-6 hex digits) and returns the
+    /// character it names.
+    fn consume_unicode_escape(&self, token: &Token) -> Result<char, Diagnostic> {
+        match self.chars.peek() {
+            Some('{') => self.advance_token(token, '{'),
+            _ => return Err(Diagnostic::new("Invalid unicode escape".to_owned(), token.range()))
+        }
+        let mut value = 0u32;
+        let mut digits = 0;
+        loop {
+            match self.chars.peek() {
+                Some(c) if c.is_ascii_hexdigit() && digits < 6 => {
+                    self.advance_token(token, c);
+                    value = value * 16 + c.to_digit(16).unwrap();
+                    digits += 1;
+                }
+                Some('}') => {
+                    self.advance_token(token, '}');
+                    break;
+                }
+                _ => return Err(Diagnostic::new("Invalid unicode escape".to_owned(), token.range()))
+            }
+        }
+        if digits == 0 { return Err(Diagnostic::new("Invalid unicode escape".to_owned(), token.range())); }
+        char::from_u32(value).ok_or_else(|| Diagnostic::new("Invalid unicode escape".to_owned(), token.range()))
+    }
+
     /// Peeks at the next 2 characters and returns them in a tuple.
     fn look2(&self) -> (Option<char>, Option<char>) {
         let peek = self.chars.peeks(2);
-        (peek[0].copied(), peek[1].copied())
+        (peek[0], peek[1])
     }
 
     /// Advance the internal file position of this Tokens instance by one character.
     /// This is used to compute the position of each Token.
-    /// 
-    /// This should be the **only** place that we call `self.chars.next()`.
+    ///
+    /// This should be the **only** place that we call `self.chars.next()`, except for
+    /// `advance_newline`, which consumes a whole (possibly two-character) newline sequence
+    /// as the single logical line break it represents.
     fn advance(&self, ch: char) {
         self.chars.next();
         if ch == '\n' {
@@ -208,16 +437,39 @@ impl Tokens<'_> {
         self.advance(ch);
     }
 
+    /// Consumes one logical newline - `\n`, `\r\n`, `\n\r`, or a bare `\r` - as a single unit,
+    /// appending its original character(s) to `token` so comment/whitespace trivia still
+    /// round-trips byte-for-byte, but advancing `FilePosition` by exactly one line no matter
+    /// how many characters the sequence spanned. Callers must only invoke this when `look2()`
+    /// confirms a newline starts at the current position.
+    fn advance_newline(&self, token: &Token) {
+        let (c0, c1) = self.look2();
+        let c0 = c0.expect("advance_newline() requires a newline to be present");
+        let len = newline_len(c0, c1).expect("advance_newline() requires a newline to be present");
+        token.push_char(c0);
+        self.chars.next();
+        if len == 2 {
+            token.push_char(c1.unwrap());
+            self.chars.next();
+        }
+        self.position = self.position.next_line();
+    }
+
     /// A single line comment is an ignored area of code delimited by a '//' sequence at the start
     /// and a new line (or the end of the file) at the end.
     /// 
     /// Consuming a single line comment cannot fail, because by definition, it can contain any character.
     fn consume_single_line_comment(&mut self) -> Option<Result<Token, Diagnostic>> {
         let token = Token::new(TokenType::Comment, self.position.clone());
-        while let Some(req) = self.chars.request() {
-            let ch = req.accept();
-            token.push_char(ch);
-            if ch == '\n' { break; }
+        loop {
+            match self.look2() {
+                (None, _) => break,
+                (Some(c0), c1) if newline_len(c0, c1).is_some() => {
+                    self.advance_newline(&token);
+                    break;
+                }
+                (Some(c), _) => self.advance_token(&token, c)
+            }
         }
         Some(Ok(token))
     }
@@ -230,20 +482,234 @@ impl Tokens<'_> {
         let token = Token::new(TokenType::Comment, self.position.clone());
         let first = self.chars.peeks(2);
         // the first two have been checked already
-        self.advance_token(&token, *first[0].unwrap());
-        self.advance_token(&token, *first[1].unwrap());
-        let mut terminated = false;
-        while let Some(ch) = self.chars.peek() {
-            self.advance_token(&token, *ch);
-            if *ch == '*' {
-                if let Some(&'/') = self.chars.peek() {
+        self.advance_token(&token, first[0].unwrap());
+        self.advance_token(&token, first[1].unwrap());
+        loop {
+            match self.look2() {
+                (None, _) => break,
+                (Some('*'), Some('/')) => {
+                    self.advance_token(&token, '*');
                     self.advance_token(&token, '/');
                     break;
                 }
+                (Some(c0), c1) if newline_len(c0, c1).is_some() => self.advance_newline(&token),
+                (Some(c), _) => self.advance_token(&token, c)
+            }
+        }
+        Some(Ok(token))
+    }
+
+    /// Consumes a reserved symbol - one of `RESERVED_SYMBOLS`. Every entry except `=`/`=>` is
+    /// exactly one character, so the common case is just emitting the character we already
+    /// peeked. `=` is the dumb one: it overlaps `OPER_CHARS`, so a following `OPER_CHARS`
+    /// character (after an optional `>` promoting it to `=>` first) means this wasn't a plain
+    /// symbol at all, and control hands off to `consume_operator_tail` instead.
+    fn consume_symbol(&self) -> Option<Result<Token, Diagnostic>> {
+        let token = Token::new(TokenType::Symbol, self.position.clone());
+        let first = self.chars.peek().unwrap();
+        self.advance_token(&token, first);
+        if first == '=' {
+            if let Some('>') = self.chars.peek() {
+                self.advance_token(&token, '>');
+            }
+            return match self.chars.peek() {
+                Some(c) if is_oper(c) => {
+                    token.set_token_type(TokenType::Oper);
+                    Some(self.consume_operator_tail(token))
+                }
+                _ => Some(Ok(token))
+            };
+        }
+        Some(Ok(token))
+    }
+
+    /// Consumes an operator - a maximal run of `OPER_CHARS`. `<` and `>` are always emitted as
+    /// their own one-character token regardless of what follows, since the parser (not the
+    /// lexer) decides whether a run like `>>` closes one generic type argument list or two.
+    fn consume_operator(&self) -> Option<Result<Token, Diagnostic>> {
+        let token = Token::new(TokenType::Oper, self.position.clone());
+        let first = self.chars.peek().unwrap();
+        self.advance_token(&token, first);
+        if first == '<' || first == '>' { return Some(Ok(token)); }
+        Some(self.consume_operator_tail(token))
+    }
+
+    /// Greedily appends further `OPER_CHARS` onto an already-started operator token, stopping
+    /// before a `<` or `>` (which always start their own token, never glue onto a run like
+    /// `=>>` or `<<`) or any character that isn't part of an operator at all.
+    fn consume_operator_tail(&self, token: Token) -> Result<Token, Diagnostic> {
+        while let Some(c) = self.chars.peek() {
+            if is_oper(c) && c != '<' && c != '>' {
+                self.advance_token(&token, c);
+            } else {
+                break;
+            }
+        }
+        Ok(token)
+    }
+
+    /// Consumes an identifier or reserved word - a maximal run of alphanumeric/`_` characters.
+    /// The dispatch in `consume_token` already confirmed the first character is a valid
+    /// identifier-starting character (`is_ident`); digits are allowed from the second character
+    /// on. Once the whole run is collected, `RESERVED` decides whether it names a keyword
+    /// rather than a plain identifier.
+    fn consume_identifier_or_reserved(&self) -> Option<Result<Token, Diagnostic>> {
+        let token = Token::new(TokenType::Ident, self.position.clone());
+        let first = self.chars.peek().unwrap();
+        self.advance_token(&token, first);
+        while let Some(c) = self.chars.peek() {
+            if is_ident(c) || kind(c) == CharKind::Num {
+                self.advance_token(&token, c);
+            } else {
+                break;
             }
         }
+        if RESERVED.contains(&token.image()) {
+            token.set_token_type(TokenType::Reserved);
+        }
         Some(Ok(token))
     }
+
+    /// Consume either: hex, binary, float, decimal.
+    ///
+    /// An underscore may separate two digits of the same run (`1_000_000`, `0xFF_FF`,
+    /// `1_000.000_1`), but can never lead, trail, double up, or sit next to the radix
+    /// prefix, decimal point, or exponent marker.
+    fn consume_number(&self) -> Option<Result<Token, Diagnostic>> {
+        let token = Token::new(TokenType::IntegerLiteral, self.position.clone());
+        let lead = self.chars.peek().unwrap();
+        self.advance_token(&token, lead);
+        if lead == '0' {
+            match self.look2() {
+                // `d == '_'` is let through here (rather than just `is_ascii_hexdigit`/`0`/`1`)
+                // so `0x_FF`/`0b_01` still commit to a radix literal instead of falling through
+                // to `consume_dec_literal` and silently re-lexing the rest as an identifier -
+                // `consume_digit_run` below rejects the leading separator itself.
+                (Some(x), Some(d)) if (x == 'x' || x == 'X') && (d.is_ascii_hexdigit() || d == '_') => {
+                    self.advance_token(&token, x);
+                    return Some(self.finish_radix_literal(token, 16, &|c| c.is_ascii_hexdigit()));
+                }
+                (Some(b), Some(d)) if (b == 'b' || b == 'B') && (d == '0' || d == '1' || d == '_') => {
+                    self.advance_token(&token, b);
+                    return Some(self.finish_radix_literal(token, 2, &|c| c == '0' || c == '1'));
+                }
+                _ => {}
+            }
+        }
+        Some(self.consume_dec_literal(token))
+    }
+
+    /// Hexadecimal and binary literals: `0[xX][0-9a-fA-F]+` or `0[bB][01]+`.
+    /// The first digit has already been confirmed by lookahead in `consume_number`.
+    fn finish_radix_literal(&self, token: Token, radix: u32, is_digit: &dyn Fn(char) -> bool) -> Result<Token, Diagnostic> {
+        self.consume_digit_run(&token, is_digit)?;
+        self.finish_integer(token, radix)
+    }
+
+    /// Decimal literals: a run of digits, optionally followed by a fractional part,
+    /// an exponent part, or both - either of which promotes the literal to a `FloatLiteral`.
+    fn consume_dec_literal(&self, token: Token) -> Result<Token, Diagnostic> {
+        self.consume_digit_run(&token, &|c| c.is_ascii_digit())?;
+        match self.look2() {
+            // `d == '_'` is let through here for the same reason it is in `consume_number` -
+            // `1._5`/`1e_5` have to commit to a float and let `consume_digit_run` reject the
+            // separator sitting right after the point/marker, instead of stopping short and
+            // silently re-lexing `._5`/`e_5` as their own tokens.
+            (Some('.'), Some(d)) if d.is_ascii_digit() || d == '_' => {
+                self.advance_token(&token, '.');
+                self.consume_float_literal(token, FloatLiteralState::Fraction)
+            }
+            (Some(e), Some(d)) if (e == 'e' || e == 'E') && (d.is_ascii_digit() || d == '_') => {
+                self.advance_token(&token, e);
+                self.consume_float_literal(token, FloatLiteralState::Exponent)
+            }
+            _ => self.finish_integer(token, 10),
+        }
+    }
+
+    /// Float literals: the whole-number portion (already consumed) plus a fractional portion
+    /// and/or an exponent portion, whichever the decimal point or `e`/`E` marker introduced.
+    fn consume_float_literal(&self, token: Token, state: FloatLiteralState) -> Result<Token, Diagnostic> {
+        self.consume_digit_run(&token, &|c| c.is_ascii_digit())?;
+        match state {
+            FloatLiteralState::Fraction => match self.look2() {
+                // see `consume_dec_literal` - `d == '_'` commits to the exponent so `1.5e_5`'s
+                // separator is rejected by `consume_digit_run` rather than stopping short.
+                (Some(e), Some(d)) if (e == 'e' || e == 'E') && (d.is_ascii_digit() || d == '_') => {
+                    self.advance_token(&token, e);
+                    self.consume_float_literal(token, FloatLiteralState::Exponent)
+                }
+                _ => self.finish_float(token),
+            }
+            FloatLiteralState::Exponent => self.finish_float(token),
+        }
+    }
+
+    /// Consumes a maximal run of digits accepted by `is_digit`, plus any `_` group separators
+    /// interspersed among them, appending each character to `token`'s image as it goes.
+    ///
+    /// Fails if a separator leads or trails the run, or if two separators appear back to back -
+    /// the three ways a `_` can appear without sitting between two digits of the same run. A
+    /// leading separator is allowed when the token already ends in a digit (the first digit of a
+    /// decimal literal, which `consume_number` consumes before this run starts).
+    fn consume_digit_run(&self, token: &Token, is_digit: &dyn Fn(char) -> bool) -> Result<(), Diagnostic> {
+        let after_digit = token.image().chars().last().map_or(false, |c| is_digit(c));
+        let mut run = String::new();
+        while let Some(ch) = self.chars.peek() {
+            if is_digit(ch) || ch == '_' {
+                run.push(ch);
+                self.advance_token(token, ch);
+            } else {
+                break;
+            }
+        }
+        if (run.starts_with('_') && !after_digit) || run.ends_with('_') || run.contains("__") {
+            return Err(Diagnostic::new(
+                "A digit separator ('_') must fall between two digits of the same literal, and cannot repeat".to_owned(),
+                token.range()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Strips separators from the accumulated image and parses it as an integer, stripping the
+    /// `0x`/`0b` radix prefix first when the literal isn't decimal.
+    fn finish_integer(&self, token: Token, radix: u32) -> Result<Token, Diagnostic> {
+        let image: String = token.image().chars().filter(|&c| c != '_').collect();
+        let digits = if radix == 10 { &image[..] } else { &image[2..] };
+        let value = match isize::from_str_radix(digits, radix) {
+            Ok(value) => value,
+            // The digit run itself is well-formed (consume_digit_run already validated that) -
+            // this is purely a magnitude problem, the same way a float literal can be
+            // syntactically fine and still not fit in its target type.
+            Err(_) => return Err(Diagnostic::new(
+                format!("Integer literal \"{}\" is too large to fit in an isize", token.image()),
+                token.range()
+            )),
+        };
+        token.set_value(TokenValue::Int(value));
+        Ok(token)
+    }
+
+    /// Strips separators from the accumulated image and parses it as a float, promoting the
+    /// token's type now that a fractional or exponent part has been seen.
+    fn finish_float(&self, token: Token) -> Result<Token, Diagnostic> {
+        token.set_token_type(TokenType::FloatLiteral);
+        let image: String = token.image().chars().filter(|&c| c != '_').collect();
+        let value: f32 = image.parse()
+            .expect("consume_float_literal only yields characters valid for a float literal");
+        token.set_value(TokenValue::Float(value));
+        Ok(token)
+    }
+}
+
+/// Tracks which portion of a float literal is currently being consumed, since the digit-run
+/// validation and exponent lookahead differ slightly between the two.
+enum FloatLiteralState {
+    /// The segment after the decimal point
+    Fraction,
+    /// The segment after the `e`/`E`
+    Exponent,
 }
 /*
 
@@ -500,5 +966,34 @@ fn is_oper(ch: char) -> bool {
 
 /// Returns true if a pair of characters represents a new line
 fn is_newline(ch: char, ch1: Option<char>) -> bool {
-    ch == '\n' || (ch == '\r' && ch1 == Some('\n'))
+    newline_len(ch, ch1).is_some()
+}
+
+/// Number of source characters consumed by the logical newline starting at `ch`, or `None`
+/// if `ch` doesn't start one. Treats `\n`, `\r\n`, the rarer `\n\r`, and the old classic-Mac
+/// bare `\r` as the same single line break, so a file's line/column bookkeeping doesn't depend
+/// on which convention it happened to be saved with.
+fn newline_len(ch: char, ch1: Option<char>) -> Option<usize> {
+    match (ch, ch1) {
+        ('\r', Some('\n')) | ('\n', Some('\r')) => Some(2),
+        ('\n', _) | ('\r', _) => Some(1),
+        _ => None
+    }
+}
+
+/// Bidirectional-control codepoints that can reorder how surrounding text *displays* without
+/// changing what it *is* - the mechanism behind "Trojan Source" source-spoofing attacks where
+/// a comment or string literal visually hides code that isn't really there.
+const BIDI_CONTROLS: [(char, char); 2] = [('\u{202A}', '\u{202E}'), ('\u{2066}', '\u{2069}')];
+
+/// Other invisible/deceptive codepoints worth flagging alongside the bidi controls: zero-width
+/// and directional marks that are easy to mistake for nothing at all, and a byte-order mark
+/// showing up somewhere other than the very start of the file.
+const OTHER_CONFUSING: [(char, char); 1] = [('\u{200B}', '\u{200F}')];
+
+/// Returns true if `ch` is a bidirectional-control or other invisible/deceptive codepoint that
+/// `allow_confusing_unicode` gates inside string literals.
+fn is_confusing_unicode(ch: char) -> bool {
+    BIDI_CONTROLS.iter().chain(OTHER_CONFUSING.iter()).any(|&(lo, hi)| ch >= lo && ch <= hi)
+        || ch == '\u{FEFF}'
 }
@@ -1,9 +1,9 @@
 use std::fmt::{self, Formatter, Display};
-use crate::core::{FilePosition, FileRange};
+use crate::core::{Diagnostic, FilePosition, FileRange};
 
 
 /// Categorizes tokens by syntactic type
-#[derive(PartialEq)]
+#[derive(Clone, PartialEq)]
 pub enum TokenType {
     None,              // default
     Comment,           // characters ignored from code
@@ -11,16 +11,19 @@ pub enum TokenType {
     Reserved,          // reserved word
     IntegerLiteral,    // integer number literals
     FloatLiteral,      // floating-point number literals
-    StringLiteral,     // character string literals
+    StringLiteral,     // a string literal with no interpolation, start to finish
+    StringFragment,    // one piece of an interpolated string, between its quote/`${`/`}` delimiters
     CharacterLiteral,  // single character literals
     Oper,              // operators
     Symbol,            // any special syntactic symbols
     Whitespace,        // any non-new-line whitespace (spaces, tabs, etc.)
     NewLine,           // \r\n and \n, has syntactic significance
     Semi,              // semicolon, special delimiter that behaves as a new line
-    Eof                // special end-of-file token
+    Eof,               // special end-of-file token
+    Error              // an unrecognized span the lexer skipped over while recovering
 }
 
+#[derive(Clone)]
 pub enum TokenValue {
     String(String),
     Char(char),
@@ -33,11 +36,15 @@ pub enum TokenValue {
 /// 'location' is the text range in the source file where the token is located
 /// 'image' is an exact copy of the token from the original source string.
 /// 'value' is an optional value that represents the parsed value of the token, if it makes sense for the token type (numbers, strings, etc.).
+#[derive(Clone)]
 pub struct Token {
     token_type: TokenType,
     start_position: FilePosition,
     image: String,
     value: Option<TokenValue>,
+    /// Set only on a `TokenType::Error` token, so a parser that walks into one can pull out
+    /// the diagnostic and resynchronize, rather than the lexer having to abort the stream.
+    error: Option<Diagnostic>,
 }
 
 impl Token {
@@ -49,7 +56,8 @@ impl Token {
             token_type,
             start_position,
             image: String::new(),
-            value: None
+            value: None,
+            error: None
         }
     }
 
@@ -62,7 +70,8 @@ impl Token {
             token_type,
             start_position,
             image: String::new(),
-            value: Some(value)
+            value: Some(value),
+            error: None
         }
     }
 
@@ -72,11 +81,30 @@ impl Token {
 
     pub fn value(&self) -> Option<&TokenValue> { self.value.as_ref() }
 
+    pub fn error(&self) -> Option<&Diagnostic> { self.error.as_ref() }
+
     pub fn range(&self) -> FileRange { self.start_position.compute_range(&self.image) }
 
     pub fn push_char(&self, ch: char) {
         self.image.push(ch);
     }
+
+    /// Sets the token type after the fact, for consumers that don't know whether a literal
+    /// is an integer or a float until they've seen the whole thing (e.g. a number's fractional
+    /// part, discovered only after the whole-number digits have already been consumed).
+    pub fn set_token_type(&self, token_type: TokenType) {
+        self.token_type = token_type;
+    }
+
+    /// Sets the parsed value of this token once its image has been fully consumed.
+    pub fn set_value(&self, value: TokenValue) {
+        self.value = Some(value);
+    }
+
+    /// Attaches the diagnostic explaining why a `TokenType::Error` token's span was rejected.
+    pub fn set_error(&self, error: Diagnostic) {
+        self.error = Some(error);
+    }
 }
 
 impl Display for Token {
@@ -84,3 +112,28 @@ impl Display for Token {
         write!(f, "{}", self.image)
     }
 }
+
+/// A single piece of insignificant text - whitespace or a comment - attached to a node rather
+/// than discarded between tokens, so the original source can be reproduced byte-for-byte.
+/// Always wraps a `Token` of type `TokenType::Whitespace`, `TokenType::NewLine`, or
+/// `TokenType::Comment`.
+#[derive(Clone)]
+pub struct Trivia {
+    token: Token,
+}
+
+impl Trivia {
+    pub fn new(token: Token) -> Trivia { Trivia { token } }
+
+    pub fn token_type(&self) -> &TokenType { self.token.token_type() }
+
+    pub fn image(&self) -> &str { self.token.image() }
+
+    pub fn range(&self) -> FileRange { self.token.range() }
+}
+
+impl Display for Trivia {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.token)
+    }
+}
@@ -1,5 +1,5 @@
 use std::{fs, path::Path, io::ErrorKind};
-use crate::{core::DiagResult, syntax::ModuleRoot};
+use crate::{core::{DiagResult, Diagnostic, FileRange}, syntax::ModuleRoot};
 use parser_new::Parser;
 
 pub mod lexer;
@@ -8,7 +8,7 @@ pub mod parser_new;
 pub mod primitives;
 pub mod token;
 
-pub fn parse_module<P: AsRef<Path>>(path: P) -> DiagResult<ModuleRoot> {
+pub fn parse_module<P: AsRef<Path>>(path: P, trace_parse: bool) -> DiagResult<ModuleRoot> {
     let path = path.as_ref();
     let text = match fs::read_to_string(path) {
         Ok(text) => text,
@@ -20,6 +20,58 @@ pub fn parse_module<P: AsRef<Path>>(path: P) -> DiagResult<ModuleRoot> {
             return DiagResult::from_error_message(msg, path);
         }
     };
-    let parser = Parser::new::<ModuleRoot>();
+    let mut parser = Parser::new::<ModuleRoot>().with_trace(trace_parse);
     parser.parse(path.as_ref(), text)
 }
+
+/// A library-friendly counterpart to `parse_module`: instead of the fail-fast `DiagResult`
+/// the compiler's `run_program` path uses (which a caller is expected to bail out on at the
+/// first error), this always hands back whatever best-effort `ModuleRoot` the parser
+/// produced - `Some` with possibly-`ErrorNode`-bearing declarations if recovery happened,
+/// `None` only if the module's outermost structure failed to parse at all - alongside the
+/// full list of diagnostics collected along the way. Intended for embedders (a language
+/// server, a linter) that want to operate on an incomplete or erroneous file rather than
+/// refuse to show anything until it's fixed.
+pub fn parse_module_lenient<P: AsRef<Path>>(path: P) -> (Option<ModuleRoot>, Vec<Diagnostic>) {
+    let path = path.as_ref();
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            let msg = match err.kind() {
+                ErrorKind::NotFound => format!("File {} not found", path.display()),
+                kind => format!("An error occurred reading file {}: {:?}", path.display(), kind),
+            };
+            return (None, vec![Diagnostic::new(msg, crate::core::FileRange::new(path.to_path_buf(), (0, 0), (0, 0)))]);
+        }
+    };
+    let mut parser = Parser::new::<ModuleRoot>();
+    let DiagResult(value, _) = parser.parse(path.as_ref(), text);
+    (value, parser.take_errors())
+}
+
+/// Re-parses after an edit to `edit`, reusing `parser`'s memo table from whatever `parse()` (or
+/// prior `reparse()`) call built it, instead of starting over from scratch.
+///
+/// `parser` must already have had an initial `parse()` call - `reparse` has nothing to reuse
+/// otherwise (see `Parser::reparse`). Only the unedited *prefix* of the file - everything before
+/// `edit`'s start - is actually served from the memo table: `ParseState::invalidate_from` drops
+/// every entry from there to the end of the file rather than just the edited span, since an
+/// edit can shift later text by a different amount than it shifted the text it replaced, and
+/// that shift isn't tracked. So this reuses work for a large file edited near the end, but a
+/// one-character edit at the top still re-parses everything after it. True subtree reuse keyed
+/// by unmoved declarations, with edit-width shift tracking, is future work.
+pub fn reparse<P: AsRef<Path>>(path: P, parser: &mut Parser<ModuleRoot>, edit: FileRange, trace_parse: bool) -> DiagResult<ModuleRoot> {
+    let path = path.as_ref();
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            let msg = match err.kind() {
+                ErrorKind::NotFound => format!("File {} not found", path.display()),
+                kind => format!("An error occurred reading file {}: {:?}", path.display(), kind),
+            };
+            return DiagResult::from_error_message(msg, path);
+        }
+    };
+    parser.set_trace(trace_parse);
+    parser.reparse(edit, text)
+}
@@ -9,7 +9,7 @@ pub enum RepeatKey {
     ZeroPlus,
 }
 
-pub type ParseFunc<T> = Box<dyn Fn(&Parser) -> Option<T>>;
+pub type ParseFunc<T> = Box<dyn Fn(&mut Parser) -> Option<T>>;
 
 // #region Parser
 
@@ -18,6 +18,12 @@ pub struct Parser {
     empty: bool,
     fail_token: Option<Token>,
     success_location: Option<FileRange>,
+    /// Every error recorded so far this parse, via `record_diagnostic` - unlike the commented
+    /// reference implementation below (which only ever surfaces whichever token the parse
+    /// happened to fail on last), this is never overwritten, so `recover_to` can record one
+    /// diagnostic per malformed declaration and `parse` can return all of them in one pass
+    /// instead of bailing at the first.
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl Parser {
@@ -26,7 +32,8 @@ impl Parser {
             tokens,
             empty: false,
             fail_token: None,
-            success_location: None
+            success_location: None,
+            diagnostics: vec![],
         }
     }
 
@@ -40,10 +47,57 @@ impl Parser {
         self.fail_token = None;
     }
 
-    pub fn parse<T>(&self, fun: ParseFunc<T>) -> DiagResult<T> where T : Syntax {
+    /// Records an error found during this parse without failing the parse itself - used by
+    /// `recover_to` so a malformed construct contributes a diagnostic instead of aborting the
+    /// rest of the file.
+    pub fn record_diagnostic(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    pub fn parse<T>(&mut self, fun: ParseFunc<T>) -> DiagResult<T> where T : Syntax {
         let result = fun(self);
-        let diagnostics: Vec<Diagnostic> = vec![];
-        todo!()
+        let diagnostics = std::mem::take(&mut self.diagnostics);
+        match result {
+            Some(value) => DiagResult::ok_with_diagnostics(value, diagnostics),
+            None => {
+                let mut diagnostics = diagnostics;
+                if let Some(token) = &self.fail_token {
+                    diagnostics.push(Diagnostic::new(format!("Unexpected \"{}\" token", token.image()), token.range()));
+                }
+                DiagResult(None, diagnostics)
+            }
+        }
+    }
+}
+
+/// Wraps `parse` with error recovery: if it fails, records a `Diagnostic` at the fail token's
+/// `FileRange` (via `Parser::record_diagnostic`) instead of propagating the failure, then
+/// advances the token stream until it reaches one of `sync` (e.g. `NewLine`, `Semi`, or a
+/// closing symbol) and resumes from there, yielding `None` in place of whatever failed to
+/// parse.
+///
+/// Wrapping each top-level declaration in this makes a single malformed declaration produce
+/// one diagnostic and a `None` placeholder instead of stopping the rest of the module from
+/// being parsed - letting `Parser::parse` collect every error found in one pass instead of
+/// bailing at the first, the way `recover` does for the packrat engine in
+/// `parser_new`/`primitives`. Nothing calls this yet: `ModuleRoot::parse_func` is built on the
+/// packrat combinators in `primitives`, not on `Parser`.
+pub fn recover_to<T>(parse: ParseFunc<T>, sync: &'static [TokenType]) -> ParseFunc<Option<T>> {
+    box move |parser: &mut Parser| -> Option<Option<T>> {
+        if let Some(value) = parse(parser) {
+            return Some(Some(value));
+        }
+        if let Some(token) = parser.fail_token.take() {
+            parser.record_diagnostic(Diagnostic::new(
+                format!("Unexpected \"{}\" token", token.image()),
+                token.range()
+            ));
+        }
+        while let Some(Ok(token)) = parser.tokens.next() {
+            if sync.contains(token.token_type()) { break; }
+        }
+        parser.succeed(None);
+        Some(None)
     }
 }
 
@@ -2,35 +2,166 @@ use core::any::TypeId;
 use core::marker::PhantomData;
 use super::lexer::Token;
 use core::any::Any;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use crate::core::DiagResult;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::core::{DiagResult, Diagnostic, FilePosition, FileRange};
 use crate::syntax::Syntax;
 
-/// A parser is a container around a particular syntax type to parse
+/// A parser is a container around a particular syntax type to parse.
+///
+/// Unlike the fail-fast `DiagResult` returned by `parse()`, a `Parser` instance keeps the
+/// diagnostics from its most recent `parse()` call around afterward, so an embedder (an IDE/
+/// language-server integration, say) can call `take_errors()` to drain them separately from
+/// the parsed tree itself - useful when the caller wants to log/display errors on its own
+/// schedule instead of only ever receiving them bundled with a `DiagResult`.
 pub struct Parser<T: Syntax> {
     _phantom: PhantomData<T>,
+    /// Diagnostics left over from the most recent `parse()` call. Drained (not copied) by
+    /// `take_errors()`, and replaced wholesale by the next `parse()` call.
+    diagnostics: Vec<Diagnostic>,
+    /// Whether to print `ParseState::trace_enter`/`trace_exit` lines during the next
+    /// `parse()` call. Set via `with_trace`, wired up to the `--trace-parse` CLI flag.
+    trace: bool,
+    /// `(hits, misses)` from the memo table of the most recent `parse()` call - see
+    /// `ParseState::memo_stats`.
+    memo_stats: (u64, u64),
+    /// The `ParseState` left over from the most recent `parse()`/`reparse()` call. Kept around
+    /// so `reparse()` has a memo table to selectively invalidate instead of building a fresh
+    /// one from scratch; `None` until the first `parse()` call.
+    state: Option<ParseState>,
 }
 
 impl<T: Syntax> Parser<T> {
     pub fn new() -> Parser<T> {
-        Parser { _phantom: PhantomData }
+        Parser { _phantom: PhantomData, diagnostics: vec![], trace: false, memo_stats: (0, 0), state: None }
     }
 
-    pub fn parse(&self, module_path: &'static Path, text: String) -> DiagResult<T> {
-        let state = ParseState::new(module_path, text);
-        match state.bk_memo::<T>(TypeId::of::<T>(), T::parse_func()) {
-            ParseResult::Success { value, .. } => DiagResult::ok(value),
-            ParseResult::Fail { expected, actual } => todo!(),
-        }
+    /// Enables (or disables) opt-in grammar-debugging trace output for this parser's
+    /// `parse()` calls - ported from the Go parser's tracing facility. When on, every
+    /// memoized rule attempt prints an indented enter/exit line naming the rule, the current
+    /// position, and whether it succeeded.
+    pub fn with_trace(mut self, trace: bool) -> Parser<T> {
+        self.trace = trace;
+        self
+    }
+
+    /// Updates this parser's trace setting in place, for a long-lived `Parser` (the kind
+    /// `reparse()` is built around) where `with_trace`'s consuming builder signature doesn't fit.
+    pub fn set_trace(&mut self, trace: bool) {
+        self.trace = trace;
+    }
+
+    pub fn parse(&mut self, module_path: &'static Path, text: String) -> DiagResult<T> {
+        let mut state = ParseState::new(module_path, text);
+        state.trace = self.trace;
+        let op = T::parse_func();
+        let result = state.bk_memo::<T>(TypeId::of::<T>(), &*op);
+        self.finish(state, result)
+    }
+
+    /// Re-parses after an edit to `edit`, reusing this `Parser`'s memo table from its last
+    /// `parse()`/`reparse()` call instead of rebuilding one from scratch: every memo entry at a
+    /// position before `edit` still describes text that didn't move, so only entries from
+    /// `edit`'s start onward are invalidated (see `ParseState::invalidate_from` for why that's
+    /// the whole suffix, not just the edited span) before the top-level rule is re-run.
+    ///
+    /// Panics if called before this `Parser` has had an initial `parse()` call to build a
+    /// `ParseState` from.
+    pub fn reparse(&mut self, edit: FileRange, new_text: String) -> DiagResult<T> {
+        let mut state = self.state.take().expect("reparse() called before an initial parse()");
+        state.trace = self.trace;
+        let invalidate_from = state.offset_of(edit.start());
+        state.set_text(new_text);
+        state.invalidate_from(invalidate_from);
+        let op = T::parse_func();
+        let result = state.bk_memo::<T>(TypeId::of::<T>(), &*op);
+        self.finish(state, result)
+    }
+
+    /// Shared bookkeeping tail for `parse()`/`reparse()`: turns the top-level `ParseResult`
+    /// into a `DiagResult`, drains this parse's diagnostics into `self`, and stashes `state` so
+    /// a later `reparse()` call has a memo table to reuse.
+    fn finish(&mut self, mut state: ParseState, result: ParseResult<T>) -> DiagResult<T> {
+        let (diag_result, diagnostics) = match result {
+            // A successful top-level parse can still have skipped over malformed
+            // declarations/statements along the way via `recover()`; surface every one of
+            // those instead of the single best-failure diagnostic (there was no outright
+            // failure to report).
+            ParseResult::Success { value, .. } => {
+                let diagnostics = state.take_recovered_diagnostics();
+                (DiagResult::ok_with_diagnostics(value, diagnostics.clone()), diagnostics)
+            },
+            ParseResult::Fail { .. } => {
+                let diagnostics = vec![state.farthest_failure_diagnostic()];
+                (DiagResult(None, diagnostics.clone()), diagnostics)
+            },
+            // A full-buffer parse always has every token available, so this can only mean the
+            // file ended mid-construct (e.g. an unclosed brace) - report it like any other
+            // unexpected end of file rather than exposing `Incomplete` to a non-streaming caller.
+            ParseResult::Incomplete { .. } => {
+                let diagnostics = vec![state.farthest_failure_diagnostic()];
+                (DiagResult(None, diagnostics.clone()), diagnostics)
+            },
+        };
+        self.memo_stats = state.memo_stats();
+        self.diagnostics = diagnostics;
+        self.state = Some(state);
+        diag_result
+    }
+
+    /// Drains the diagnostics left over from the most recent `parse()` call, without
+    /// consuming the `Parser` itself - the same instance can go on to `parse()` further text
+    /// (e.g. the next edit to a file open in an editor).
+    pub fn take_errors(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// `(hits, misses)` for the memo table of the most recent `parse()` call - see
+    /// `ParseState::memo_stats`.
+    pub fn memo_stats(&self) -> (u64, u64) {
+        self.memo_stats
     }
 }
 
+/// Uniquely identifies one anonymous parse operation for memoization purposes, for
+/// operations built by combinators (`seq`, `repeat`, etc.) that have no `Syntax` impl and
+/// therefore no `TypeId` of their own to key on.
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct OperationId(u64);
+
+static NEXT_OPERATION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Allocates a fresh, process-wide-unique `OperationId`. A combinator should call this once,
+/// when the combinator itself is built, and close over the result — every invocation of that
+/// one combinator instance (at whatever positions the grammar ends up trying it) must share
+/// a single id, or each attempt would get its own memo cache entry and nothing would be saved.
+pub fn next_operation_id() -> OperationId {
+    OperationId(NEXT_OPERATION_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Identifies a memoizable parse rule: either a named `Syntax` type (keyed by its own
+/// `TypeId`) or an anonymous combinator-built operation (keyed by an explicitly allocated
+/// `OperationId`).
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub enum ParseMemoId {
+    Syntax(TypeId),
+    Operation(OperationId),
+}
+
+impl From<TypeId> for ParseMemoId {
+    fn from(id: TypeId) -> ParseMemoId { ParseMemoId::Syntax(id) }
+}
+
+impl From<OperationId> for ParseMemoId {
+    fn from(id: OperationId) -> ParseMemoId { ParseMemoId::Operation(id) }
+}
+
 /// Identifies a particular "parsing position" for memoization. This includes:
-/// 1. The ID of a syntax type being parsed
+/// 1. The ID of the rule being parsed (a `Syntax` type or an anonymous combinator operation)
 /// 2. A position in the file text
-#[derive(Eq, Hash, PartialEq)]
-struct ParseMemoKey(TypeId, usize);
+#[derive(Clone, Eq, Hash, PartialEq)]
+struct ParseMemoKey(ParseMemoId, usize);
 
 pub struct ParseState {
     /// Offset from the beginning of the file (used for reading and backtracking)
@@ -43,58 +174,268 @@ pub struct ParseState {
     column: usize,
     /// Full text of the module file being parsed
     chars: Vec<char>,
-    /// Cache for memoization
+    /// Cache for memoization, keyed by rule and input position so the same nonterminal is
+    /// never re-parsed at the same location - without it, an ambiguous grammar like the
+    /// expression/type hierarchy in `SyntaxType` would re-attempt the same alternative
+    /// exponentially many times instead of once.
     memo_cache: HashMap<ParseMemoKey, ParseResult<Box<dyn Any>>>,
+    /// Number of `bk_memo` calls served from `memo_cache` without re-running the rule.
+    memo_hits: u64,
+    /// Number of `bk_memo` calls that found no cached entry and had to run the rule.
+    memo_misses: u64,
+    /// Stack of keys currently being evaluated by `bk_memo`, in call order. Used to detect
+    /// left recursion: a rule that recurses into a key already on this stack is a
+    /// left-recursive reference (to itself if it's the top frame, or indirectly through one
+    /// or more other rules otherwise), and must fail immediately so the seed-growing loop for
+    /// whichever frame it refers back to can make progress.
+    lr_stack: Vec<ParseMemoKey>,
+    /// For a key that heads a left-recursive seed-growing loop (i.e. appears as the target of
+    /// a detected recursion), every other key found sitting between its frame and the
+    /// recursive reference back to it - these are the rules invoked indirectly through the
+    /// cycle. Their memo entries must be invalidated before each re-run of the head, or a
+    /// nested rule would keep serving the result it computed against the previous, weaker
+    /// seed instead of being re-evaluated against the grown one.
+    involved: HashMap<ParseMemoKey, HashSet<ParseMemoKey>>,
+    /// The farthest position any sub-parser has failed at so far.
+    farthest_fail_position: usize,
+    /// The set of all `expected` strings reported by failures at `farthest_fail_position`.
+    /// Reset whenever a failure is recorded past the previous farthest point, and
+    /// accumulated into whenever one is recorded at exactly that point.
+    farthest_expected: HashSet<String>,
+    /// The token (image + range) that was actually found at `farthest_fail_position`, if any.
+    farthest_actual: Option<(String, FileRange)>,
+    /// Diagnostics emitted by `recover()` when it skips over a malformed construct. Unlike
+    /// `farthest_fail_position`/`farthest_expected` (which only ever keep the single best
+    /// diagnostic for an outright, unrecovered failure), every one of these is kept: a
+    /// recovered error doesn't stop the rest of the file from being parsed, so none of them
+    /// should be dropped in favor of another.
+    recovered_diagnostics: Vec<Diagnostic>,
+    /// Set via `Parser::with_trace`, opt-in grammar debugging mode (`--trace-parse`): every
+    /// memoized rule attempt prints an indented enter/exit line naming the rule, the current
+    /// `FilePosition`, and whether the attempt succeeded - useful for debugging the ordering
+    /// constraints (the many "must be before X" comments) between alternatives in
+    /// `parseType`/`parseExpression`.
+    trace: bool,
+    /// Current nesting depth of trace output, incremented on enter and decremented on exit.
+    trace_indent: usize,
 }
 
 impl ParseState {
     pub fn new(module_path: &'static Path, text: String) -> ParseState {
+        let chars: Vec<char> = text.chars().collect();
+        // Pre-size the memo table off the character count rather than growing it one rehash at a
+        // time: a fully-memoized parse has at most one entry per rule per character position,
+        // so the character count is a reasonable upper-bound estimate of the table's eventual
+        // size for any single rule, and cheap insurance against rehashing on a large file.
+        let memo_capacity = chars.len();
         ParseState {
             position: 0,
             module_path,
             line: 1,
             column: 1,
-            chars: text.chars().collect(),
-            memo_cache: HashMap::new()
+            chars,
+            memo_cache: HashMap::with_capacity(memo_capacity),
+            memo_hits: 0,
+            memo_misses: 0,
+            lr_stack: Vec::new(),
+            involved: HashMap::new(),
+            farthest_fail_position: 0,
+            farthest_expected: HashSet::new(),
+            farthest_actual: None,
+            recovered_diagnostics: vec![],
+            trace: false,
+            trace_indent: 0,
         }
     }
 
+    /// Prints an indented trace "enter" line for `label` at the current position, if tracing
+    /// (`--trace-parse`) is enabled. A no-op otherwise.
+    pub fn trace_enter(&mut self, label: &str) {
+        if !self.trace { return; }
+        println!("{}-> {} at ({}, {})", "  ".repeat(self.trace_indent), label, self.line, self.column);
+        self.trace_indent += 1;
+    }
+
+    /// Prints an indented trace "exit" line for `label`, reporting whether the attempt
+    /// succeeded, if tracing (`--trace-parse`) is enabled. A no-op otherwise.
+    pub fn trace_exit(&mut self, label: &str, success: bool) {
+        if !self.trace { return; }
+        self.trace_indent = self.trace_indent.saturating_sub(1);
+        println!("{}<- {} at ({}, {}): {}", "  ".repeat(self.trace_indent), label, self.line, self.column, if success { "ok" } else { "fail" });
+    }
+
+    /// Records a diagnostic produced while recovering from a malformed construct. Called by
+    /// the `recover()` combinator; kept separate from `record_failure()` because a recovered
+    /// error doesn't backtrack the parser and must never be dropped in favor of some other
+    /// failure at a farther position.
+    pub fn push_recovered_diagnostic(&mut self, diagnostic: Diagnostic) {
+        self.recovered_diagnostics.push(diagnostic);
+    }
+
+    /// Drains every diagnostic collected by `recover()` over the course of this parse.
+    pub fn take_recovered_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.recovered_diagnostics)
+    }
+
+    /// Returns `(hits, misses)` for the memo table so far - how many `bk_memo` calls were
+    /// served from `memo_cache` versus had to actually run the rule. Exposed so tests can
+    /// assert a grammar stays linear (a high hit rate) instead of just trusting the algorithm.
+    pub fn memo_stats(&self) -> (u64, u64) {
+        (self.memo_hits, self.memo_misses)
+    }
+
     /// Calls the provided function with bookkeeping wrapped around it.
     /// This ensures that a failed result properly backtracs the parser to
     /// the point it was at before the function was called.
     pub fn bk<T: Any>(&mut self, op: Box<dyn ParseOperation<T>>) -> ParseResult<T> {
         let current = self.position;
         let result = op(&mut self);
-        if let ParseResult::Fail { .. } = result {
-            self.position = current;
+        match &result {
+            ParseResult::Fail { expected, actual } => {
+                self.record_failure(expected, actual);
+                self.position = current;
+            }
+            ParseResult::Incomplete { .. } => { self.position = current; }
+            ParseResult::Success { .. } => {}
         }
         result
     }
 
+    /// Records a failure reported at the parser's current position, keeping track of
+    /// only the farthest point any failure has been reported at. Failures before that
+    /// point are dropped, failures at exactly that point are merged into the accumulated
+    /// `expected` set, and a failure past that point resets the tracker to start fresh.
+    /// This produces a single, high-quality diagnostic instead of reporting whichever
+    /// alternative happened to fail last.
+    fn record_failure(&mut self, expected: &str, actual: &Option<Token>) {
+        if self.position > self.farthest_fail_position {
+            self.farthest_fail_position = self.position;
+            self.farthest_expected.clear();
+            self.farthest_expected.insert(expected.to_string());
+            self.farthest_actual = actual.as_ref().map(|t| (t.image().to_string(), t.range()));
+        } else if self.position == self.farthest_fail_position {
+            self.farthest_expected.insert(expected.to_string());
+            if self.farthest_actual.is_none() {
+                self.farthest_actual = actual.as_ref().map(|t| (t.image().to_string(), t.range()));
+            }
+        }
+    }
+
+    /// Synthesizes a single `Diagnostic` from the accumulated farthest-failure state:
+    /// the token found at the farthest position reached (or end-of-file, if none), and a
+    /// deduplicated, sorted "expected one of: a, b, c" list drawn from every failure that
+    /// was reported at that position.
+    pub fn farthest_failure_diagnostic(&self) -> Diagnostic {
+        let mut expected: Vec<&String> = self.farthest_expected.iter().collect();
+        expected.sort();
+        let expected_list = expected.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ");
+        match &self.farthest_actual {
+            Some((image, range)) => Diagnostic::new(
+                format!("Unexpected \"{}\" token, expected one of: {}", image, expected_list),
+                range.clone()
+            ),
+            None => Diagnostic::new_from_position(
+                format!("Unexpected end of file, expected one of: {}", expected_list),
+                FilePosition::new(self.module_path, (self.line, self.column))
+            ),
+        }
+    }
+
     /// Similar to `bk()`, but also uses the memoization cache.
     /// This will check the memoization cache before calling the function,
     /// returning an existing result if it exists and automatically
     /// advancing the parser.
     /// If there is no existing value, the result of the function
     /// will be stored in the cache.
-    pub fn bk_memo<T: Syntax>(&mut self, id: TypeId, fun: Box<dyn ParseOperation<T>>) -> ParseResult<T> {
-        let key = ParseMemoKey(id, self.position);
+    ///
+    /// Also implements the Warth/Douglass/Millstein "seed-growing" algorithm for left
+    /// recursion: a failing seed is stored for a rule before it runs, so a left-recursive
+    /// reference back into it fails and lets the rest of the alternative produce a first
+    /// match. Each match that grows the seed triggers a re-run, until growth stops.
+    ///
+    /// Indirect left recursion (`A` calls `B` calls `A`) is handled via `involved`: every
+    /// key between a rule's frame and a recursive reference back to it has its memo entry
+    /// invalidated before each re-run, so it doesn't keep serving a result computed against
+    /// the un-grown seed.
+    pub fn bk_memo<T: Any + Clone>(&mut self, id: impl Into<ParseMemoId>, fun: &dyn ParseOperation<T>) -> ParseResult<T> {
+        let key = ParseMemoKey(id.into(), self.position);
         if let Some(result) = self.memo_cache.get(&key) {
-            match result {
-                ParseResult::Success { size, value } => {
-                    self.advance(*size);
-                    ParseResult::Success {
-                        value: *value.downcast().expect(&format!("Downcast failed: expected {:?}, received {:?}", TypeId::of::<T>(), value.type_id())),
-                        size: *size
-                    }
-                },
-                ParseResult::Fail { expected, actual } => ParseResult::Fail { expected: *expected, actual: *actual }
+            self.memo_hits += 1;
+            // A seed is planted here before `lr_stack` is pushed below, so a recursive
+            // reference back to a rule still being grown always hits this branch. Record
+            // involved frames now, while we can still tell this is a recursive hit.
+            if let Some(head_index) = self.lr_stack.iter().position(|k| k == &key) {
+                let involved: Vec<ParseMemoKey> = self.lr_stack[head_index + 1..].to_vec();
+                if !involved.is_empty() {
+                    self.involved.entry(key.clone()).or_insert_with(HashSet::new).extend(involved);
+                }
+            }
+            // `result` only ever borrows the cache entry (the same key can be hit again later,
+            // directly or via a sibling alternative at the same position), so extracting a typed
+            // value out of it has to clone rather than move - `downcast` does exactly that.
+            let result: ParseResult<T> = result.downcast::<T>();
+            if let ParseResult::Success { size, .. } = &result {
+                self.advance(*size);
+            }
+            return result;
+        }
+
+        self.memo_misses += 1;
+        let start = self.position;
+        // plant the seed: a failure, so the first (recursive) call to this rule bails out
+        self.memo_cache.insert(key.clone(), ParseResult::Fail { expected: String::new(), actual: None }.upcast());
+        self.lr_stack.push(key.clone());
+
+        // `fun` is borrowed (not consumed) on each attempt so it can be re-run as the seed grows
+        let run = |state: &mut ParseState| -> ParseResult<T> {
+            let current = state.position;
+            let result = fun(state);
+            match &result {
+                ParseResult::Fail { expected, actual } => {
+                    state.record_failure(expected, actual);
+                    state.position = current;
+                }
+                ParseResult::Incomplete { .. } => { state.position = current; }
+                ParseResult::Success { .. } => {}
             }
-        } else {
-            let result = self.bk(fun);
-            self.memo_cache.insert(key, result.upcast());
             result
+        };
+
+        let mut best = run(self);
+        // Ran out of input mid-rule: don't commit a memo entry for this position at all, so a
+        // later re-parse with more of the buffer available re-attempts the rule from scratch
+        // instead of replaying a verdict made with incomplete information.
+        if matches!(&best, ParseResult::Incomplete { .. }) {
+            self.lr_stack.pop();
+            self.involved.remove(&key);
+            self.memo_cache.remove(&key);
+            return best;
+        }
+        loop {
+            let grew = matches!(&best, ParseResult::Success { size, .. } if *size > 0);
+            if !grew { break; }
+            // store the current best as the seed and retry from the start,
+            // giving the left-recursive reference a chance to consume it and grow
+            self.memo_cache.insert(key.clone(), best.upcast());
+            // rules invoked indirectly through this cycle must be forgotten too, so they're
+            // re-evaluated against the grown seed instead of replaying a stale cached result
+            if let Some(involved) = self.involved.get(&key) {
+                for k in involved.clone() { self.memo_cache.remove(&k); }
+            }
+            self.position = start;
+            let attempt = run(self);
+            let advanced_further = match (&attempt, &best) {
+                (ParseResult::Success { size: new_size, .. }, ParseResult::Success { size: old_size, .. }) => new_size > old_size,
+                _ => false,
+            };
+            if !advanced_further { self.position = start + best.consumed(); break; }
+            best = attempt;
         }
+
+        self.lr_stack.pop();
+        self.involved.remove(&key);
+        self.memo_cache.insert(key, best.upcast());
+        best
     }
 
     /// Grabs the next character from the list and advances the following:
@@ -118,6 +459,93 @@ impl ParseState {
     fn advance(&mut self, count: usize) {
         self.position += count;
     }
+
+    /// The current `(line, column)` position as a `FilePosition`, for diagnostics a combinator
+    /// raises directly (e.g. `recover`'s "skipped this span" diagnostic) rather than
+    /// accumulated through `record_failure`/`farthest_failure_diagnostic`.
+    pub fn current_position(&self) -> FilePosition {
+        FilePosition::new(self.module_path, (self.line, self.column))
+    }
+
+    /// Peeks at the next character without consuming it, or `None` at end of input.
+    pub fn peek_char(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    /// Whether the upcoming, unconsumed text starts with `token`, without consuming any of it
+    /// or disturbing `position`. Used by combinators that need to peek ahead for a
+    /// synchronization token (`recover`) without the false failures a probe built from
+    /// `term`/`bk` would record against `farthest_fail_position`.
+    pub fn starts_with(&self, token: &str) -> bool {
+        let mut rest = token.chars();
+        self.chars[self.position..].iter().zip(&mut rest).all(|(&a, b)| a == b) && rest.next().is_none()
+    }
+
+    /// Runs `op` with the same backtracking guarantee `bk`/`bk_memo` give a boxed operation: if
+    /// `op` fails (or runs out of input), `position` is restored to what it was before `op`
+    /// ran. Takes `op` by value as a plain closure (rather than `Box<dyn ParseOperation<T>>`)
+    /// so a combinator built from several sub-operations (`seq2`, `seq3`, `opt`,
+    /// `repeat_zero`, ...) can compose them inline without heap-allocating a box per sub-step.
+    pub fn backtracking<T: Any>(&mut self, op: impl FnOnce(&mut ParseState) -> ParseResult<T>) -> ParseResult<T> {
+        let current = self.position;
+        let result = op(self);
+        match &result {
+            ParseResult::Fail { expected, actual } => {
+                self.record_failure(expected, actual);
+                self.position = current;
+            }
+            ParseResult::Incomplete { .. } => { self.position = current; }
+            ParseResult::Success { .. } => {}
+        }
+        result
+    }
+
+    /// Converts a `(line, column)` position into an absolute character offset into `self.chars`,
+    /// by counting forward from the start of the file - used by `reparse` to translate an
+    /// edit's start position into the same offset space the memo table's keys are in.
+    pub fn offset_of(&self, position: (usize, usize)) -> usize {
+        let (target_line, target_column) = position;
+        let mut line = 1;
+        let mut column = 1;
+        for (offset, &c) in self.chars.iter().enumerate() {
+            if line == target_line && column == target_column { return offset; }
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        self.chars.len()
+    }
+
+    /// Drops every memo entry (and left-recursion bookkeeping entry) from `offset` through the
+    /// end of the file, along with the farthest-failure tracking, so a `reparse` doesn't reuse a
+    /// memoized result that spans or follows an edit.
+    ///
+    /// This is conservative rather than precise: an edit can shift every character after it by a
+    /// different amount than it shifted the text it replaced, which would leave memo keys past
+    /// the edit pointing at the wrong offsets if they were kept. Without tracking that shift,
+    /// the only entries safe to reuse are the ones entirely before `offset` - the unedited
+    /// prefix - so that's the only region `reparse` actually benefits from.
+    pub fn invalidate_from(&mut self, offset: usize) {
+        self.memo_cache.retain(|key, _| key.1 < offset);
+        self.involved.retain(|key, _| key.1 < offset);
+        self.farthest_fail_position = 0;
+        self.farthest_expected = HashSet::new();
+        self.farthest_actual = None;
+    }
+
+    /// Replaces the text being parsed and resets the cursor to the start of it, for `reparse` to
+    /// call once the memo table has been invalidated - the reused entries all key off position 0
+    /// being the start of file, so the cursor has to restart there too even though the reused
+    /// prefix means most of it will be served straight out of the memo table.
+    pub fn set_text(&mut self, text: String) {
+        self.chars = text.chars().collect();
+        self.position = 0;
+        self.line = 1;
+        self.column = 1;
+    }
 }
 
 /// A function that performs a parse operation using a parser,
@@ -144,26 +572,58 @@ pub enum ParseResult<T: Any> {
         expected: String,
         actual: Option<Token>,
     },
+    /// Distinct from `Fail`: the rule didn't fail, it simply ran out of input before it could
+    /// decide - the incremental/streaming mode feeds a `ParseState` only the tokens available
+    /// so far (e.g. the text typed before the cursor in an editor), so reaching the end of
+    /// that buffer mid-rule is not the same as the buffer genuinely not matching. `needed` is
+    /// a lower bound on how many more tokens would let the rule make progress, surfaced so a
+    /// caller like an LSP can request exactly that much more text before retrying rather than
+    /// re-parsing from scratch.
+    Incomplete {
+        needed: usize,
+    },
 }
 
 impl<T: Any> ParseResult<T> {
-    /// Converts this ParseResult to one 
+    /// The number of characters consumed by this result (0 for a `Fail` or `Incomplete`).
+    fn consumed(&self) -> usize {
+        match self {
+            ParseResult::Success { size, .. } => *size,
+            ParseResult::Fail { .. } => 0,
+            ParseResult::Incomplete { .. } => 0,
+        }
+    }
+}
+
+impl<T: Any + Clone> ParseResult<T> {
+    /// Converts this `ParseResult<T>` to a type-erased `ParseResult<Box<dyn Any>>` suitable for
+    /// `memo_cache`, which has to hold every rule's result behind one concrete type regardless
+    /// of what `T` it was parsed as. Only borrows `self` - the same entry is looked up again on
+    /// every later cache hit at this key, so it has to stay intact rather than being consumed
+    /// here, which is why `T` has to be `Clone`.
     pub fn upcast(&self) -> ParseResult<Box<dyn Any>> {
         match self {
-            ParseResult::Success { value, size } => ParseResult::Success { value: box *value, size: *size },
-            ParseResult::Fail { expected, actual } => ParseResult::Fail { expected: *expected, actual: *actual }
+            ParseResult::Success { value, size } => ParseResult::Success { value: Box::new(value.clone()), size: *size },
+            ParseResult::Fail { expected, actual } => ParseResult::Fail { expected: expected.clone(), actual: actual.clone() },
+            ParseResult::Incomplete { needed } => ParseResult::Incomplete { needed: *needed },
         }
     }
 }
 
 impl ParseResult<Box<dyn Any>> {
-    pub fn downcast<T: Any>(&self) -> ParseResult<T> {
+    /// The other half of `upcast`: recovers a `ParseResult<T>` from a type-erased memo entry.
+    /// Takes `&self` rather than `self` for the same reason `upcast` does - the cache keeps
+    /// its entry - so pulling `T` back out of the boxed `dyn Any` has to go through
+    /// `downcast_ref` (which only borrows) followed by a clone, instead of `Box::downcast`
+    /// (which would consume the `Box` this method doesn't own).
+    pub fn downcast<T: Any + Clone>(&self) -> ParseResult<T> {
         match self {
             ParseResult::Success { size, value } => ParseResult::Success {
-                value: *value.downcast().expect(&format!("Downcast failed: expected {:?}, received {:?}", TypeId::of::<T>(), value.type_id())),
+                value: value.downcast_ref::<T>().expect(&format!("Downcast failed: expected {:?}, received {:?}", TypeId::of::<T>(), value.type_id())).clone(),
                 size: *size
             },
-            ParseResult::Fail { expected, actual } => ParseResult::Fail { expected: *expected, actual: *actual }
+            ParseResult::Fail { expected, actual } => ParseResult::Fail { expected: expected.clone(), actual: actual.clone() },
+            ParseResult::Incomplete { needed } => ParseResult::Incomplete { needed: *needed },
         }
     }
 }
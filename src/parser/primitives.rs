@@ -1,12 +1,43 @@
 use crate::{syntax::Syntax, parser::parser_new::ParseOperation};
-use super::{parser_new::{ParseState, ParseResult}, lexer::Token};
-use std::any::{TypeId, Any};
+use super::{parser_new::{next_operation_id, ParseState, ParseResult}, lexer::{Token, TokenType, Trivia}};
+use crate::core::{Applicability, Diagnostic, DiagnosticLevel, FileRange};
+use std::any::{TypeId, Any, type_name};
+use std::cell::Cell;
 
 /// Wraps a `Syntax` type's parse operation with the necessary book-keeping.
+///
+/// Every named rule in the grammar is attempted through this function, which makes it the one
+/// place to hook `--trace-parse` debugging into: each attempt prints an indented enter/exit
+/// line (via `ParseState::trace_enter`/`trace_exit`, a no-op unless tracing is on) naming the
+/// rule's type, so the ordering constraints between alternatives (the many "must be before X"
+/// comments in `parseType`/`parseExpression`) can be observed directly instead of inferred.
 pub fn parse<T: Syntax>() -> Box<dyn ParseOperation<T>> {
-    let op = T::parse_func();
-    box |state| {
-        state.bk_memo(TypeId::of::<T>(), op)
+    // Every node's surrounding whitespace/comments are captured here, rather than requiring
+    // every `Syntax::parse_func()` impl to wrap itself in `with_trivia` individually - this is
+    // the one place a `T: Syntax` value comes into existence, so it's the one place that can
+    // call `Syntax::with_surrounding_trivia` generically.
+    let op = with_trivia(T::parse_func(), |value: T, leading, trailing| value.with_surrounding_trivia(leading, trailing));
+    let label = type_name::<T>();
+    box move |state| {
+        state.trace_enter(label);
+        let result = state.bk_memo(TypeId::of::<T>(), &*op);
+        state.trace_exit(label, matches!(result, ParseResult::Success { .. }));
+        result
+    }
+}
+
+/// Wraps an anonymous, combinator-built parse operation (one with no `Syntax` impl of its
+/// own, and so no `TypeId` to key a memo cache entry on) so repeated attempts at the same
+/// file position are served from cache instead of re-running `op`.
+///
+/// Call this once per combinator instance — e.g. inside `repeat`/`seq`/`choice` themselves,
+/// around whatever operation they're given — not once per parse attempt, since the
+/// `OperationId` it allocates must stay the same across every attempt for the cache to do
+/// anything.
+pub fn memoize<T: Any + Clone>(op: Box<dyn ParseOperation<T>>) -> Box<dyn ParseOperation<T>> {
+    let id = next_operation_id();
+    box move |state| {
+        state.bk_memo(id, &*op)
     }
 }
 
@@ -18,24 +49,29 @@ pub fn transform<T1: Any, T2: Any>(
     box move |state| {
         match parse(state) {
             ParseResult::Success { value, size } => ParseResult::Success { value: transform(value), size },
-            ParseResult::Fail { expected, actual } => ParseResult::Fail { expected, actual }
+            ParseResult::Fail { expected, actual } => ParseResult::Fail { expected, actual },
+            ParseResult::Incomplete { needed } => ParseResult::Incomplete { needed },
         }
     }
 }
 
 /// Parses a terminal symbol, yielding a string.
 pub fn term(terminal: &'static str) -> Box<dyn ParseOperation<String>> {
-    // The basic idea is that we check 
+    // The basic idea is that we check
     box |state: &mut ParseState| {
-        let s = String::new();
-        for ch in terminal.chars() {
+        let mut s = String::new();
+        let chars: Vec<char> = terminal.chars().collect();
+        for (i, &ch) in chars.iter().enumerate() {
             if let Some(actual) = state.next() {
                 if actual == ch { s.push(actual); }
                 else {
                     return ParseResult::Fail { expected: terminal.to_string(), actual: None };
                 }
             } else {
-                return ParseResult::Fail { expected: terminal.to_string(), actual: None };
+                // Ran out of buffered input partway through matching the terminal, rather
+                // than finding a character that doesn't match - in streaming mode this means
+                // "ask for more text and retry", not "this terminal doesn't match here".
+                return ParseResult::Incomplete { needed: chars.len() - i };
             }
         }
         ParseResult::Success { value: s, size: s.len() }
@@ -46,10 +82,20 @@ pub fn term(terminal: &'static str) -> Box<dyn ParseOperation<String>> {
 pub fn chars(start: char, end: char) -> Box<dyn ParseOperation<char>> { todo!() }
 
 /// Parses a `Token`, converting the resulting `String` to a `Token`.
-pub fn tok(parse: impl ParseOperation<String>) -> Box<dyn ParseOperation<Token>> { todo!() }
+pub fn tok(parse: impl ParseOperation<String> + 'static) -> Box<dyn ParseOperation<Token>> { todo!() }
 
-/// Parses the end of the file.
-pub fn eof() -> Box<dyn ParseOperation<Token>> { todo!() }
+/// Parses the end of the file, yielding a zero-width `TokenType::Eof` token at the current
+/// position - the terminal every top-level `Syntax::parse_func` (e.g. `ModuleRoot`'s) is
+/// expected to require after its last repeated construct, so trailing garbage the grammar
+/// doesn't recognize fails the parse instead of being silently left unconsumed.
+pub fn eof() -> Box<dyn ParseOperation<Token>> {
+    box |state| {
+        match state.peek_char() {
+            None => ParseResult::Success { value: Token::new(TokenType::Eof, state.current_position()), size: 0 },
+            Some(_) => ParseResult::Fail { expected: "end of file".to_string(), actual: None },
+        }
+    }
+}
 
 /// Parses the end of a line (OS-sensitive).
 pub fn eol() -> Box<dyn ParseOperation<String>> { todo!() }
@@ -71,53 +117,93 @@ macro_rules! seq {
 }
 
 pub fn seq2<T1: 'static, T2: 'static>(
-    parse1: impl ParseOperation<T1>,
-    parse2: impl ParseOperation<T2>,
-) -> Box<dyn ParseOperation<(T1, T2)>> { todo!() }
+    parse1: impl ParseOperation<T1> + 'static,
+    parse2: impl ParseOperation<T2> + 'static,
+) -> Box<dyn ParseOperation<(T1, T2)>> {
+    box move |state| {
+        state.backtracking(|state| {
+            let (value1, size1) = match parse1(state) {
+                ParseResult::Success { value, size } => (value, size),
+                ParseResult::Fail { expected, actual } => return ParseResult::Fail { expected, actual },
+                ParseResult::Incomplete { needed } => return ParseResult::Incomplete { needed },
+            };
+            let (value2, size2) = match parse2(state) {
+                ParseResult::Success { value, size } => (value, size),
+                ParseResult::Fail { expected, actual } => return ParseResult::Fail { expected, actual },
+                ParseResult::Incomplete { needed } => return ParseResult::Incomplete { needed },
+            };
+            ParseResult::Success { value: (value1, value2), size: size1 + size2 }
+        })
+    }
+}
 
 pub fn seq3<T1: 'static, T2: 'static, T3: 'static>(
-    parse1: impl ParseOperation<T1>,
-    parse2: impl ParseOperation<T2>,
-    parse3: impl ParseOperation<T3>,
-) -> Box<dyn ParseOperation<(T1, T2, T3)>> { todo!() }
+    parse1: impl ParseOperation<T1> + 'static,
+    parse2: impl ParseOperation<T2> + 'static,
+    parse3: impl ParseOperation<T3> + 'static,
+) -> Box<dyn ParseOperation<(T1, T2, T3)>> {
+    box move |state| {
+        state.backtracking(|state| {
+            let (value1, size1) = match parse1(state) {
+                ParseResult::Success { value, size } => (value, size),
+                ParseResult::Fail { expected, actual } => return ParseResult::Fail { expected, actual },
+                ParseResult::Incomplete { needed } => return ParseResult::Incomplete { needed },
+            };
+            let (value2, size2) = match parse2(state) {
+                ParseResult::Success { value, size } => (value, size),
+                ParseResult::Fail { expected, actual } => return ParseResult::Fail { expected, actual },
+                ParseResult::Incomplete { needed } => return ParseResult::Incomplete { needed },
+            };
+            let (value3, size3) = match parse3(state) {
+                ParseResult::Success { value, size } => (value, size),
+                ParseResult::Fail { expected, actual } => return ParseResult::Fail { expected, actual },
+                ParseResult::Incomplete { needed } => return ParseResult::Incomplete { needed },
+            };
+            ParseResult::Success { value: (value1, value2, value3), size: size1 + size2 + size3 }
+        })
+    }
+}
 
 pub fn seq4<T1: 'static, T2: 'static, T3: 'static, T4: 'static>(
-    parse1: impl ParseOperation<T1>,
-    parse2: impl ParseOperation<T2>,
-    parse3: impl ParseOperation<T3>,
-    parse4: impl ParseOperation<T4>,
+    parse1: impl ParseOperation<T1> + 'static,
+    parse2: impl ParseOperation<T2> + 'static,
+    parse3: impl ParseOperation<T3> + 'static,
+    parse4: impl ParseOperation<T4> + 'static,
 ) -> Box<dyn ParseOperation<(T1, T2, T3, T4)>> { todo!() }
 
 pub fn seq5<T1: 'static, T2: 'static, T3: 'static, T4: 'static, T5: 'static>(
-    parse1: impl ParseOperation<T1>,
-    parse2: impl ParseOperation<T2>,
-    parse3: impl ParseOperation<T3>,
-    parse4: impl ParseOperation<T4>,
-    parse5: impl ParseOperation<T5>,
+    parse1: impl ParseOperation<T1> + 'static,
+    parse2: impl ParseOperation<T2> + 'static,
+    parse3: impl ParseOperation<T3> + 'static,
+    parse4: impl ParseOperation<T4> + 'static,
+    parse5: impl ParseOperation<T5> + 'static,
 ) -> Box<dyn ParseOperation<(T1, T2, T3, T4, T5)>> { todo!() }
 
 pub fn seq6<T1: 'static, T2: 'static, T3: 'static, T4: 'static, T5: 'static, T6: 'static>(
-    parse1: impl ParseOperation<T1>,
-    parse2: impl ParseOperation<T2>,
-    parse3: impl ParseOperation<T3>,
-    parse4: impl ParseOperation<T4>,
-    parse5: impl ParseOperation<T5>,
-    parse6: impl ParseOperation<T6>,
+    parse1: impl ParseOperation<T1> + 'static,
+    parse2: impl ParseOperation<T2> + 'static,
+    parse3: impl ParseOperation<T3> + 'static,
+    parse4: impl ParseOperation<T4> + 'static,
+    parse5: impl ParseOperation<T5> + 'static,
+    parse6: impl ParseOperation<T6> + 'static,
 ) -> Box<dyn ParseOperation<(T1, T2, T3, T4, T5, T6)>> { todo!() }
 
 pub fn seq7<T1: 'static, T2: 'static, T3: 'static, T4: 'static, T5: 'static, T6: 'static, T7: 'static>(
-    parse1: impl ParseOperation<T1>,
-    parse2: impl ParseOperation<T2>,
-    parse3: impl ParseOperation<T3>,
-    parse4: impl ParseOperation<T4>,
-    parse5: impl ParseOperation<T5>,
-    parse6: impl ParseOperation<T6>,
-    parse7: impl ParseOperation<T7>,
+    parse1: impl ParseOperation<T1> + 'static,
+    parse2: impl ParseOperation<T2> + 'static,
+    parse3: impl ParseOperation<T3> + 'static,
+    parse4: impl ParseOperation<T4> + 'static,
+    parse5: impl ParseOperation<T5> + 'static,
+    parse6: impl ParseOperation<T6> + 'static,
+    parse7: impl ParseOperation<T7> + 'static,
 ) -> Box<dyn ParseOperation<(T1, T2, T3, T4, T5, T6, T7)>> { todo!() }
 
 /// Parses the first successful expression in a list of expressions.
-/// 
+///
 /// All parse functions must return the same type.
+///
+/// Each choice is typically built from `parse::<T>()`, so `--trace-parse` already sees every
+/// alternative attempted here as its own enter/exit line, in the order they're tried.
 #[macro_export]
 macro_rules! choice {
     ($($choices:expr),*) => {
@@ -125,23 +211,112 @@ macro_rules! choice {
     };
 }
 
-pub fn choice<T: 'static>(choices: Vec<impl ParseOperation<T>>) -> Box<dyn ParseOperation<T>> { todo!() }
+pub fn choice<T: 'static>(choices: Vec<impl ParseOperation<T> + 'static>) -> Box<dyn ParseOperation<T>> {
+    box move |state| {
+        let mut expected = Vec::new();
+        for alternative in &choices {
+            match state.backtracking(|state| alternative(state)) {
+                success @ ParseResult::Success { .. } => return success,
+                ParseResult::Incomplete { needed } => return ParseResult::Incomplete { needed },
+                ParseResult::Fail { expected: exp, .. } => expected.push(exp),
+            }
+        }
+        // None of the alternatives matched at this position - report them all rather than
+        // just the last one tried, the same way `Representation::Choice::to_ebnf` renders a
+        // `choice!` as `a | b | c` instead of picking one.
+        ParseResult::Fail { expected: expected.join(" | "), actual: None }
+    }
+}
 
 /// Parses zero or more instances of an expression.
-/// 
+///
 /// The yielded value is a vector of all parsed instances.
-pub fn repeat_zero<T: 'static>(parse: impl ParseOperation<T>) -> Box<dyn ParseOperation<Vec<T>>> { todo!() }
+pub fn repeat_zero<T: 'static>(parse: impl ParseOperation<T> + 'static) -> Box<dyn ParseOperation<Vec<T>>> {
+    box move |state| {
+        let mut values = Vec::new();
+        let mut total_size = 0;
+        loop {
+            match state.backtracking(|state| parse(state)) {
+                // A match that consumes nothing would repeat forever; treat it the same as a
+                // failed match and stop, rather than hanging.
+                ParseResult::Success { size: 0, .. } => break,
+                ParseResult::Success { value, size } => {
+                    values.push(value);
+                    total_size += size;
+                }
+                ParseResult::Fail { .. } => break,
+                ParseResult::Incomplete { needed } => return ParseResult::Incomplete { needed },
+            }
+        }
+        ParseResult::Success { value: values, size: total_size }
+    }
+}
 
 /// Parses one or more instances of an expression.
-/// 
+///
 /// The yielded value is a vector of all parsed instances.
-pub fn repeat_one<T: 'static>(parse: impl ParseOperation<T>) -> Box<dyn ParseOperation<Vec<T>>> { todo!() }
+pub fn repeat_one<T: 'static>(parse: impl ParseOperation<T> + 'static) -> Box<dyn ParseOperation<Vec<T>>> { todo!() }
+
+/// How many repetitions `repeat_sep` requires before it succeeds.
+pub enum RepeatBase {
+    /// `T (sep T)*` - zero or more, succeeding with an empty `Vec` if `T` never matches.
+    ZeroPlus,
+    /// `T (sep T)*`, requiring at least one `T` - fails outright if the first doesn't match.
+    OnePlus,
+}
+
+/// Parses `parse` interleaved with `sep`, discarding the separators and yielding the parsed
+/// values - the desugaring the commented-out TS reference implementation above sketches as
+/// `T(* sep s) => (T (s T)*)?` and `T(+ sep s) => T (s T)*`, built on `seq`/`opt`/`repeat_zero`
+/// rather than hand-written per construct.
+///
+/// A trailing separator is rejected by default: after the last successfully parsed `T`, `sep`
+/// is only consumed if another `T` follows it, so e.g. `(a, b,)` does not parse as `[a, b]`
+/// with a dangling `,` silently discarded. Every comma-separated grammar construct in
+/// `SyntaxType` (`TupleLiteral`, `StructLiteral`, `ArrayLiteral`, function `Param` lists) is
+/// expected to be built on this instead of repeating the `T (sep T)*` shape by hand.
+pub fn repeat_sep<T: 'static, S: 'static>(
+    parse: impl ParseOperation<T> + Clone + 'static,
+    sep: impl ParseOperation<S> + Clone + 'static,
+    base: RepeatBase,
+) -> Box<dyn ParseOperation<Vec<T>>> {
+    let rest = repeat_zero(transform(seq2(sep, parse.clone()), |(_, value)| value));
+    let one_plus = transform(seq2(parse, rest), |(first, mut rest): (T, Vec<T>)| {
+        let mut all = vec![first];
+        all.append(&mut rest);
+        all
+    });
+    match base {
+        RepeatBase::OnePlus => one_plus,
+        RepeatBase::ZeroPlus => transform(opt(one_plus), |found: Option<Vec<T>>| found.unwrap_or_else(Vec::new)),
+    }
+}
+
+/// Parses `open`, then `body`, then `close`, discarding the bracketing tokens and yielding
+/// only `body`'s value - the shared basis for every bracketed construct (parenthesized
+/// expressions, `[...]` array literals, `{...}` struct literals) so each one doesn't have to
+/// re-derive how to discard the brackets from a `seq3`.
+pub fn delimited<O: 'static, T: 'static, C: 'static>(
+    open: impl ParseOperation<O> + 'static,
+    body: impl ParseOperation<T> + 'static,
+    close: impl ParseOperation<C> + 'static,
+) -> Box<dyn ParseOperation<T>> {
+    transform(seq3(open, body, close), |(_, value, _)| value)
+}
 
 /// Parses an expression where it can either be present or not present.
 /// 
 /// The yielded value is an `Option` containin either the parsed value
 /// or `None` if the value could not be parsed.
-pub fn opt<T: 'static>(parse: impl ParseOperation<T>) -> Box<dyn ParseOperation<Option<T>>> { todo!() }
+pub fn opt<T: 'static>(parse: impl ParseOperation<T> + 'static) -> Box<dyn ParseOperation<Option<T>>> {
+    box move |state| {
+        match state.backtracking(|state| parse(state)) {
+            ParseResult::Success { value, size } => ParseResult::Success { value: Some(value), size },
+            ParseResult::Fail { .. } => ParseResult::Success { value: None, size: 0 },
+            ParseResult::Incomplete { needed } => ParseResult::Incomplete { needed },
+        }
+    }
+}
 
 /// Parses an expression without consuming characters.
 /// 
@@ -150,7 +325,7 @@ pub fn opt<T: 'static>(parse: impl ParseOperation<T>) -> Box<dyn ParseOperation<
 /// If the expression fails to parse, this expression will also fail.
 /// 
 /// This is useful for ensuring that an expression is followed by another expression.
-pub fn and<T: 'static>(parse: impl ParseOperation<T>) -> Box<dyn ParseOperation<()>> { todo!() }
+pub fn and<T: 'static>(parse: impl ParseOperation<T> + 'static) -> Box<dyn ParseOperation<()>> { todo!() }
 
 /// Ensures that an expression cannot be parsed at the current position,
 /// without consuming characters.
@@ -160,4 +335,266 @@ pub fn and<T: 'static>(parse: impl ParseOperation<T>) -> Box<dyn ParseOperation<
 /// If the expression fails to parse, nothing happens and an empty tuple is yielded.
 /// 
 /// This is useful for ensuring that an expression is NOT followed by another expression.
-pub fn not<T: 'static>(parse: impl ParseOperation<T>) -> Box<dyn ParseOperation<()>> { todo!() }
+pub fn not<T: 'static>(parse: impl ParseOperation<T> + 'static) -> Box<dyn ParseOperation<()>> { todo!() }
+
+/// A structural, walkable description of what a combinator accepts, independent of how it's
+/// actually parsed - the same shape as the grammar it was built from, so it can be rendered as
+/// EBNF (via `ebnf`) or turned into a human-readable name for `ParseResult::Fail`'s `expected`
+/// field (e.g. `NonTerminal("ImportDeclaration")` reading as `expected ImportDeclaration`
+/// instead of whatever raw terminal happened to fail deepest inside it).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Representation {
+    Terminal(String),
+    NonTerminal(&'static str),
+    Sequence(Vec<Representation>),
+    Choice(Vec<Representation>),
+    Repeat { base: Box<Representation>, min: usize },
+    Optional(Box<Representation>),
+    And(Box<Representation>),
+    Not(Box<Representation>),
+}
+
+impl Representation {
+    /// Renders this `Representation` as the right-hand side of an EBNF rule, e.g.
+    /// `Sequence([NonTerminal("Import"), Terminal(";")])` becomes `Import, ";"`.
+    pub fn to_ebnf(&self) -> String {
+        match self {
+            Representation::Terminal(s) => format!("\"{}\"", s),
+            Representation::NonTerminal(name) => name.to_string(),
+            Representation::Sequence(parts) => parts.iter().map(Representation::to_ebnf).collect::<Vec<_>>().join(", "),
+            Representation::Choice(parts) => parts.iter().map(Representation::to_ebnf).collect::<Vec<_>>().join(" | "),
+            Representation::Repeat { base, min } => {
+                let inner = base.to_ebnf();
+                if *min == 0 { format!("{{{}}}", inner) } else { format!("{}, {{{}}}", inner, inner) }
+            }
+            Representation::Optional(base) => format!("[{}]", base.to_ebnf()),
+            Representation::And(base) => format!("&({})", base.to_ebnf()),
+            Representation::Not(base) => format!("!({})", base.to_ebnf()),
+        }
+    }
+}
+
+/// Walks every named rule's `Representation` and renders the whole grammar as EBNF, one
+/// `Rule = ... ;` line per entry, in the order given - the intended source for both a
+/// user-facing "dump the grammar" command and the non-terminal names fed into
+/// `ParseResult::Fail`'s `expected` field (see `Representation::NonTerminal`).
+pub fn ebnf(rules: &[(&'static str, Representation)]) -> String {
+    rules.iter()
+        .map(|(name, rep)| format!("{} = {} ;", name, rep.to_ebnf()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parses a non-terminal: a named rule defined elsewhere in the grammar, referenced here by
+/// its `Syntax` type rather than inlined.
+///
+/// This is what `seq`/`choice`/`repeat` are expected to wrap a sub-rule reference in (as
+/// opposed to `term`/`chars`, which describe the input directly) so the resulting failure's
+/// `expected` field reads as the rule's name - e.g. `expected ImportDeclaration` - instead of
+/// whichever terminal deepest inside that rule happened to fail. Identical to `parse::<T>()`
+/// in every other respect (memoization, `--trace-parse` tracing); the two are expected to
+/// converge once `ParseOperation` carries a `Representation` of its own, which would let
+/// `expected` be populated here instead of needing every leaf combinator to know its
+/// enclosing rule's name.
+pub fn non_term<T: Syntax>() -> Box<dyn ParseOperation<T>> {
+    let op = parse::<T>();
+    let representation = Representation::NonTerminal(type_name::<T>());
+    box move |state| {
+        match op(state) {
+            ParseResult::Fail { actual, .. } => ParseResult::Fail { expected: representation.to_ebnf(), actual },
+            other => other,
+        }
+    }
+}
+
+/// Wraps `parse` with error recovery: if it fails, instead of propagating the failure, this
+/// records a diagnostic on the parser `state` (via `ParseState::push_recovered_diagnostic`),
+/// skips tokens up to (but not including) the next one that appears in `sync_set`, and
+/// succeeds with an error value spanning the skipped range - `sync_set` is typically a
+/// statement/declaration terminator or the token that starts the next construct (`;`, `}`,
+/// a declaration keyword), so whatever called this (usually `repeat`/`repeat_zero`) can pick
+/// back up there instead of aborting the rest of the file over one malformed construct.
+///
+/// Every caller of `recover` is expected to yield a type with some "this didn't parse" case
+/// (an `ErrorNode`-carrying variant of the relevant `Declaration`/`Statement`/`Expression`/
+/// `Node` union) to hold the recovered span; `recover` itself is generic over that yielded
+/// type and has no opinion on how it's constructed.
+pub fn recover<T: From<FileRange> + 'static>(parse: impl ParseOperation<T> + 'static, sync_set: &'static [&'static str]) -> Box<dyn ParseOperation<T>> {
+    box move |state| {
+        match state.backtracking(|state| parse(state)) {
+            success @ ParseResult::Success { .. } => success,
+            ParseResult::Incomplete { needed } => ParseResult::Incomplete { needed },
+            ParseResult::Fail { expected, .. } => {
+                let start = state.current_position();
+                // Skip forward one character at a time until a synchronization token is found
+                // (or the file runs out), so whatever called this - usually `repeat`/
+                // `repeat_zero` trying the next declaration/statement - picks back up there
+                // instead of the rest of the file being abandoned over this one malformed
+                // construct.
+                let mut skipped = 0;
+                while state.peek_char().is_some() && !sync_set.iter().any(|token| state.starts_with(token)) {
+                    state.next();
+                    skipped += 1;
+                }
+                let end = state.current_position();
+                let range = FileRange::new(start.path().to_path_buf(), start.position(), end.position());
+                state.push_recovered_diagnostic(
+                    Diagnostic::new(format!("expected {}, skipping to the next synchronization point", expected), range.clone())
+                        .with_level(DiagnosticLevel::Error)
+                );
+                ParseResult::Success { value: T::from(range), size: skipped }
+            }
+        }
+    }
+}
+
+/// The reading `disambiguate_angle_brackets` settled on for a leading `<`.
+pub enum AngleBracketReading<T, U> {
+    /// `<` was accepted as the start of a type-argument list (a `SpecificType`/
+    /// `FunctionApplication` suffix).
+    TypeArgs(T),
+    /// `<` was read as the less-than comparison operator of a `BinaryExpression`.
+    LessThan(U),
+}
+
+/// Rustc-style disambiguation between a `<...>` type-argument list (for `SpecificType`/
+/// `FunctionApplication`) and a `<` that starts a `BinaryExpression` less-than comparison -
+/// both readings can begin with the same token (e.g. `a < b > (c)` is genuinely ambiguous), so
+/// this doesn't commit to whichever alternative happens to be tried first the way a plain
+/// `select`/`choice` would.
+///
+/// `type_args` is speculatively parsed first. That reading is accepted only if it succeeds
+/// *and* the closing `>` is immediately followed by one of `continuation` - a token that can
+/// only continue a type application, e.g. `(` for a call. Otherwise the attempt is discarded,
+/// the parser backtracks to the `<`, and `less_than` is run to parse it as a comparison
+/// operator instead.
+///
+/// When `type_args` parses cleanly but isn't followed by a valid continuation token, both
+/// readings were genuinely viable at this `<` and the comparison reading was chosen by default;
+/// in that case this records a `Warning`-level `Diagnostic` (via `state.push_recovered_diagnostic`)
+/// spanning the `<` alone (not the closing `>`), explaining that `<` was interpreted as the
+/// start of a comparison rather than type arguments, with a `MaybeIncorrect` suggestion that
+/// replaces that `<` with a `(...)` placeholder; it does not name the left operand to
+/// parenthesize.
+///
+/// Has no live call site yet: the real expression grammar (`FunctionApplication` vs
+/// `BinaryExpression` suffixes) isn't written, only the placeholder types in `syntax::mod`, so
+/// nothing currently builds the `type_args`/`less_than` operations this would disambiguate
+/// between. Implemented ahead of that grammar the same way the rest of this module's
+/// not-yet-wired combinators are.
+pub fn disambiguate_angle_brackets<T: 'static, U: 'static>(
+    type_args: impl ParseOperation<T> + 'static,
+    continuation: &'static [&'static str],
+    less_than: impl ParseOperation<U> + 'static,
+) -> Box<dyn ParseOperation<AngleBracketReading<T, U>>> {
+    box move |state| {
+        // Set when `type_args` parses cleanly but isn't followed by a valid continuation: that
+        // reading is discarded below (by failing, so `backtracking` restores the position to
+        // before the `<`), but it's still the genuinely-ambiguous case that deserves a warning,
+        // as opposed to `type_args` simply failing outright.
+        let ambiguous = Cell::new(false);
+        let type_args_reading = state.backtracking(|state| {
+            match type_args(state) {
+                ParseResult::Success { value, size } => {
+                    if continuation.iter().any(|token| state.starts_with(token)) {
+                        ParseResult::Success { value, size }
+                    } else {
+                        ambiguous.set(true);
+                        ParseResult::Fail { expected: "a type-argument continuation".to_string(), actual: None }
+                    }
+                }
+                other => other,
+            }
+        });
+        match type_args_reading {
+            ParseResult::Success { value, size } => ParseResult::Success { value: AngleBracketReading::TypeArgs(value), size },
+            ParseResult::Incomplete { needed } => ParseResult::Incomplete { needed },
+            ParseResult::Fail { .. } => {
+                if ambiguous.get() {
+                    let position = state.current_position();
+                    let range = position.compute_range("<");
+                    state.push_recovered_diagnostic(
+                        Diagnostic::new(
+                            "`<` was interpreted as the less-than operator, not the start of a type-argument list".to_string(),
+                            range.clone()
+                        )
+                            .with_level(DiagnosticLevel::Warning)
+                            .with_suggestion(range, "(...)".to_string(), Applicability::MaybeIncorrect)
+                    );
+                }
+                match less_than(state) {
+                    ParseResult::Success { value, size } => ParseResult::Success { value: AngleBracketReading::LessThan(value), size },
+                    ParseResult::Fail { expected, actual } => ParseResult::Fail { expected, actual },
+                    ParseResult::Incomplete { needed } => ParseResult::Incomplete { needed },
+                }
+            }
+        }
+    }
+}
+
+/// Wraps `parse` so that the whitespace and newline trivia immediately preceding and following
+/// the parsed construct is captured from the token stream and attached to the resulting node
+/// via `attach`, instead of being silently discarded between tokens - following
+/// rust-analyzer's lossless syntax-tree design, this is how every node ends up able to answer
+/// `Syntax::leading_trivia`/`Syntax::trailing_trivia` and be reproduced byte-for-byte by
+/// `syntax::to_source`. Comments are not captured: `consume_trivia` stops at the first
+/// character that isn't whitespace.
+///
+/// `parse::<T>()` wraps every `Syntax` node's operation in this (rather than nodes doing it
+/// themselves); `seq`/`choice`/`repeat` do not call it. Trailing trivia is taken after every
+/// node, so trivia between two sibling nodes ends up attached to the earlier one.
+pub fn with_trivia<T: 'static>(
+    parse: impl ParseOperation<T> + 'static,
+    attach: impl Fn(T, Vec<Trivia>, Vec<Trivia>) -> T + 'static,
+) -> Box<dyn ParseOperation<T>> {
+    box move |state| {
+        let leading = consume_trivia(state);
+        let leading_size: usize = leading.iter().map(|t| t.image().chars().count()).sum();
+        match parse(state) {
+            ParseResult::Success { value, size } => {
+                let trailing = consume_trivia(state);
+                let trailing_size: usize = trailing.iter().map(|t| t.image().chars().count()).sum();
+                ParseResult::Success {
+                    value: attach(value, leading, trailing),
+                    size: leading_size + size + trailing_size,
+                }
+            }
+            ParseResult::Fail { expected, actual } => ParseResult::Fail { expected, actual },
+            ParseResult::Incomplete { needed } => ParseResult::Incomplete { needed },
+        }
+    }
+}
+
+/// Consumes a maximal run of whitespace/newline trivia starting at `state`'s current position,
+/// yielding each contiguous run of non-newline whitespace (`TokenType::Whitespace`) or each
+/// individual `\n` (`TokenType::NewLine`) as its own `Trivia`, in source order. Stops at the
+/// first character that isn't trivia, or at end of input. Used by `with_trivia` to capture the
+/// whitespace immediately surrounding a node instead of letting it fall on the floor between
+/// tokens.
+fn consume_trivia(state: &mut ParseState) -> Vec<Trivia> {
+    let mut trivia = Vec::new();
+    loop {
+        match state.peek_char() {
+            Some(c) if c == '\n' => {
+                let token = Token::new(TokenType::NewLine, state.current_position());
+                token.push_char(c);
+                state.next();
+                trivia.push(Trivia::new(token));
+            }
+            Some(c) if c.is_whitespace() => {
+                let token = Token::new(TokenType::Whitespace, state.current_position());
+                while let Some(c) = state.peek_char() {
+                    if c.is_whitespace() && c != '\n' {
+                        token.push_char(c);
+                        state.next();
+                    } else {
+                        break;
+                    }
+                }
+                trivia.push(Trivia::new(token));
+            }
+            _ => break,
+        }
+    }
+    trivia
+}
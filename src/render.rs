@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::{Diagnostic, DiagnosticLevel};
+
+/// How much terminal color to use when rendering diagnostics, set by `--color=auto|always|never`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn parse(value: &str) -> Option<ColorMode> {
+        match value {
+            "auto" => Some(ColorMode::Auto),
+            "always" => Some(ColorMode::Always),
+            "never" => Some(ColorMode::Never),
+            _ => None,
+        }
+    }
+
+    /// `auto` always resolves to "on": there's no terminal-detection crate in this tree to ask
+    /// whether stderr is actually a tty, and diagnostics are only ever printed to stderr, so
+    /// treating `auto` as `always` is the closest honest approximation until one is added.
+    fn enabled(self) -> bool {
+        self != ColorMode::Never
+    }
+}
+
+/// Caches each diagnosed file's source text - split into lines - so a file referenced by many
+/// diagnostics is only read off disk once, keyed by the same `PathBuf` a `Diagnostic`'s span
+/// names. A file that fails to read is cached as `None` so a second diagnostic pointing at it
+/// doesn't retry the read, it just falls back to the plain format again.
+pub struct SourceMap {
+    files: HashMap<PathBuf, Option<Vec<String>>>,
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap { files: HashMap::new() }
+    }
+
+    fn lines(&mut self, path: &Path) -> Option<&[String]> {
+        self.files
+            .entry(path.to_path_buf())
+            .or_insert_with(|| fs::read_to_string(path).ok().map(|text| text.lines().map(String::from).collect()))
+            .as_deref()
+    }
+}
+
+/// Renders `diagnostic` as a numbered source snippet around its span, underlined with carets and
+/// colorized by its `DiagnosticLevel`, reading (and caching, via `source_map`) the file its span
+/// names. Degrades to the diagnostic's plain `Display` line whenever the file can't be read or
+/// its span falls outside the file's current line count.
+pub fn render_diagnostic(diagnostic: &Diagnostic, source_map: &mut SourceMap, color: ColorMode) -> String {
+    let path = diagnostic.location.path().to_path_buf();
+    let (start_line, start_col) = diagnostic.location.start();
+    let (end_line, end_col) = diagnostic.location.end();
+
+    let lines = match source_map.lines(&path) {
+        Some(lines) if start_line < lines.len() => lines,
+        _ => return format!("{}", diagnostic),
+    };
+    let last_line = end_line.min(lines.len() - 1);
+    let gutter_width = (last_line + 1).to_string().len();
+
+    let mut out = String::new();
+    out.push_str(&colorize(&format!("{:?}: {}", diagnostic.level, diagnostic.message), diagnostic.level, color));
+    out.push_str(&format!("\n{:width$} --> {}:{}:{}\n", "", path.display(), start_line + 1, start_col + 1, width = gutter_width));
+
+    for line_no in start_line..=last_line {
+        let text = &lines[line_no];
+        out.push_str(&format!("{:>width$} | {}\n", line_no + 1, text, width = gutter_width));
+
+        let underline_start = if line_no == start_line { start_col } else { 0 };
+        let underline_end = if line_no == end_line { end_col + 1 } else { text.len() };
+        let underline_end = underline_end.max(underline_start + 1);
+        let carets = format!("{}{}", " ".repeat(underline_start), "^".repeat(underline_end - underline_start));
+        out.push_str(&format!("{:width$} | {}\n", "", colorize(&carets, diagnostic.level, color), width = gutter_width));
+    }
+
+    if let Some(suggestion) = &diagnostic.suggestion {
+        out.push_str(&format!("  = help: did you mean `{}`?\n", suggestion));
+    }
+    for suggestion in &diagnostic.suggestions {
+        out.push_str(&format!("  = help: try replacing with `{}`\n", suggestion.replacement));
+    }
+
+    out
+}
+
+fn colorize(text: &str, level: DiagnosticLevel, color: ColorMode) -> String {
+    if !color.enabled() { return text.to_string(); }
+    let code = match level {
+        DiagnosticLevel::Fatal | DiagnosticLevel::Error => "31",
+        DiagnosticLevel::Warning => "33",
+        DiagnosticLevel::Message => "36",
+        DiagnosticLevel::Verbose => "90",
+    };
+    format!("\x1b[{}m{}\x1b[0m", code, text)
+}
@@ -1,29 +1,66 @@
+use std::io::{self, BufRead, Write};
 use std::path::PathBuf;
 
 use crate::core::{Diagnostic, DiagnosticLevel, DiagResult};
+use crate::fix::apply_fixes;
+use crate::message_format::{render_diagnostic_json, sort_for_output, MessageFormat};
+use crate::render::{render_diagnostic, ColorMode, SourceMap};
 use crate::semantic::{analyze, program::Program};
+use crate::semantic::repl::ReplSession;
 
-pub fn run_program(path: PathBuf, args: &[String]) -> i32 {
+pub fn run_program(path: PathBuf, args: &[String], trace_parse: bool, color: ColorMode, message_format: MessageFormat, fix: bool) -> i32 {
     // perform type checking on the specified path, which will enumerate all modules in the Program
-    let DiagResult(program, diags) = analyze(path);
+    let DiagResult(mut program, mut diags) = analyze(path.clone(), trace_parse);
+
+    // `--fix`: rewrite every machine-applicable suggestion in place, then re-analyze the same
+    // path so the diagnostics and `Program` used below reflect what's left after the fix, not
+    // what was true before it - mirroring `cargo fix`, which never reports against stale
+    // diagnostics once it's rewritten the source they were raised against
+    if fix {
+        let touched = apply_fixes(&diags);
+        if !touched.is_empty() {
+            for path in &touched {
+                eprintln!("fixed: {}", path.display());
+            }
+            let DiagResult(rechecked_program, rechecked_diags) = analyze(path.clone(), trace_parse);
+            program = rechecked_program;
+            diags = rechecked_diags;
+        }
+    }
+
     // we will eventually provide a verbosity option, but for now just set it to Message
-    let diags: Vec<&Diagnostic> = diags.iter()
+    let mut diags: Vec<&Diagnostic> = diags.iter()
         .filter(|d| { d.level >= DiagnosticLevel::Message })
         .collect();
     let errCount = diags.iter().filter(|d| { d.level >= DiagnosticLevel::Error }).count();
     let warnCount = diags.iter().filter(|d| { d.level == DiagnosticLevel::Warning }).count();
-    if errCount > 0 {
-        // there were errors, print all messages and exit
-        eprintln!("Errors: {}, Warnings: {}\n\n", errCount, warnCount);
-        eprintln!("{}", diags.iter().map(|d| { format!("{}\n", d) }).collect::<Vec<String>>().join(""));
-        eprintln!("\nCompilation failed\n");
-        return 1;
-    } else if diags.len() > 0 {
-        // otherwise, just print all messages and continue
-        eprintln!("Warnings: {}\n\n", warnCount);
-        eprintln!("{}", diags.iter().map(|d| { format!("{}\n", d) }).collect::<Vec<String>>().join(""));
-        let suffix = if warnCount > 0 { " with warnings" } else { "" };
-        eprintln!("\nCompilation succeeded{}\n\n", suffix);
+
+    if message_format == MessageFormat::Json {
+        // one JSON object per line, sorted deterministically so golden comparisons are stable,
+        // rather than the grouped "Errors: N, Warnings: N" blocks the human format prints
+        sort_for_output(&mut diags);
+        for diag in &diags {
+            println!("{}", render_diagnostic_json(diag));
+        }
+        if errCount > 0 { return 1; }
+    } else {
+        let mut source_map = SourceMap::new();
+        let render_all = |diags: &[&Diagnostic], source_map: &mut SourceMap| {
+            diags.iter().map(|d| format!("{}\n", render_diagnostic(d, source_map, color))).collect::<Vec<String>>().join("")
+        };
+        if errCount > 0 {
+            // there were errors, print all messages and exit
+            eprintln!("Errors: {}, Warnings: {}\n\n", errCount, warnCount);
+            eprintln!("{}", render_all(&diags, &mut source_map));
+            eprintln!("\nCompilation failed\n");
+            return 1;
+        } else if diags.len() > 0 {
+            // otherwise, just print all messages and continue
+            eprintln!("Warnings: {}\n\n", warnCount);
+            eprintln!("{}", render_all(&diags, &mut source_map));
+            let suffix = if warnCount > 0 { " with warnings" } else { "" };
+            eprintln!("\nCompilation succeeded{}\n\n", suffix);
+        }
     }
     // semantically good, translate the program
     let executable = translate(program.unwrap());
@@ -31,6 +68,30 @@ pub fn run_program(path: PathBuf, args: &[String]) -> i32 {
     interpret(executable, args)
 }
 
+/// Reads expressions from stdin one line at a time, evaluating each against a `ReplSession` that
+/// keeps every name bound by a prior line in scope for the next one, printing that line's
+/// diagnostics - if any - without ending the session over them the way `run_program` does for a
+/// whole file.
+pub fn repl(trace_parse: bool) -> i32 {
+    let mut session = ReplSession::new(trace_parse);
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush().ok();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            // EOF
+            return 0;
+        }
+        let line = line.trim_end_matches('\n').to_string();
+        if line.is_empty() { continue; }
+        let diags = session.submit_line(line);
+        for diag in diags.iter().filter(|d| d.level >= DiagnosticLevel::Message) {
+            eprintln!("{}", diag);
+        }
+    }
+}
+
 // TODO
 struct Executable;
 fn translate(_program: Program) -> Executable { unimplemented!() }
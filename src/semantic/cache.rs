@@ -0,0 +1,76 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::fs;
+
+/// A content-addressed cache of resolved modules, keyed by a hash of the module's own
+/// source rather than its path, so an unchanged module hits the cache even if it's imported
+/// under several different paths (symlinks, re-exported packages, etc.).
+///
+/// TODO: this hashes the module's normalized source text, not its parsed AST, because
+/// `syntax::ModuleRoot`'s fields aren't accessible outside the `syntax` module yet (see the
+/// same caveat in `loader::import_targets`). Once that's wired up, `hash_source` should hash
+/// the AST instead, so formatting-only edits that don't change the source's meaning still
+/// invalidate correctly while whitespace/comment-only diffs that `normalize_source` already
+/// strips do not.
+pub struct ImportCache {
+    dir: PathBuf,
+    entries: HashMap<String, PathBuf>,
+}
+
+impl ImportCache {
+    pub fn new(dir: PathBuf) -> ImportCache {
+        ImportCache { dir, entries: HashMap::new() }
+    }
+
+    /// Returns the path previously cached under `hash`, if any, meaning it can be reused
+    /// without re-parsing its module.
+    pub fn lookup(&self, hash: &str) -> Option<&PathBuf> {
+        self.entries.get(hash)
+    }
+
+    /// Records that `path`'s module hashed to `hash`, both in memory for this run and on
+    /// disk (as a marker file named after the hash, holding the module's path) so later runs can tell a hash has
+    /// been seen before.
+    pub fn record(&mut self, hash: String, path: PathBuf) {
+        let _ = fs::create_dir_all(&self.dir);
+        let _ = fs::write(self.dir.join(&hash), path.to_string_lossy().as_bytes());
+        self.entries.insert(hash, path);
+    }
+
+    /// Checks a resolved module's hash against a declared integrity annotation
+    /// (`ImportDeclaration::integrity`), returning `true` if they match or no annotation was
+    /// given.
+    pub fn verify_integrity(hash: &str, declared: Option<&str>) -> bool {
+        match declared {
+            Some(declared) => strip_algorithm_prefix(declared) == hash,
+            None => true,
+        }
+    }
+}
+
+fn strip_algorithm_prefix(declared: &str) -> &str {
+    match declared.find(':') {
+        Some(idx) => &declared[idx + 1..],
+        None => declared,
+    }
+}
+
+/// Hashes `source` after trimming each line and dropping blank lines and lines that are
+/// entirely a `//` comment, so re-indenting a module or adding blank lines or whole-line
+/// comments doesn't invalidate modules that depend on it. Whitespace inside a line, a trailing
+/// `//` comment and a `/* */` comment still change the hash.
+pub fn hash_source(source: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    normalize_source(source).hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+fn normalize_source(source: &str) -> String {
+    source.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
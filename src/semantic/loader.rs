@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use crate::core::{Diagnostic, DiagResult, FileRange};
+use crate::parser::parse_module;
+use crate::syntax::ModuleRoot;
+
+pub struct ModuleGraph {
+    pub modules: HashMap<PathBuf, ModuleRoot>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// One entry of the loader's work stack: the path to load, and the chain of modules (root
+/// first) that imported their way down to it. The chain is what lets us tell a cycle apart
+/// from an already-loaded module: either can cause `stack.push` to see a path it's seen
+/// before, but only the former is an error.
+struct WorkItem {
+    path: PathBuf,
+    chain: Vec<PathBuf>,
+}
+
+/// Loads a root `.ren` file and the full transitive closure of modules it imports.
+///
+/// This walks an explicit stack rather than recursing, so the per-module "chain so far"
+/// used for cycle detection is plain data instead of borrowed call frames, and a long linear
+/// import chain can't blow the native stack. Before a dependency is pushed, its path is
+/// checked against the current chain; if it's already there, the cycle is reported at the
+/// importing location instead of being pushed (which would recurse forever).
+pub fn load_module_graph(root: PathBuf) -> DiagResult<ModuleGraph> {
+    let mut modules = HashMap::new();
+    let mut diagnostics = vec![];
+    let mut stack = vec![WorkItem { path: root, chain: vec![] }];
+
+    while let Some(WorkItem { path, chain }) = stack.pop() {
+        if modules.contains_key(&path) { continue; }
+
+        // not driven by the `--trace-parse` CLI flag - this loader isn't wired into the
+        // `run_program` path that flag controls
+        let module = match parse_module(&path, false) {
+            DiagResult(Some(module), diags) => { diagnostics.extend(diags); module }
+            DiagResult(None, diags) => { diagnostics.extend(diags); continue; }
+        };
+
+        let mut next_chain = chain.clone();
+        next_chain.push(path.clone());
+
+        for (target_path, target_location) in import_targets(&module) {
+            if chain.contains(&target_path) {
+                diagnostics.push(Diagnostic::new(
+                    format!(
+                        "Circular import: \"{}\" imports \"{}\", which already appears earlier in this import chain",
+                        path.display(), target_path.display()
+                    ),
+                    target_location
+                ));
+                continue;
+            }
+            stack.push(WorkItem { path: target_path, chain: next_chain.clone() });
+        }
+
+        modules.insert(path, module);
+    }
+
+    DiagResult(Some(ModuleGraph { modules, diagnostics }), vec![])
+}
+
+/// Resolves every import of `module` to an absolute path and the location of the import
+/// that names it.
+///
+/// TODO: `ModuleRoot` doesn't parse imports at all yet (see its own doc comment), so there's
+/// no way to walk `module`'s real imports here even though `syntax::ImportDeclaration` now
+/// carries real fields (`module_name`, `imports`, `integrity`) for when it can. Once it does,
+/// each import should be resolved via `resolver::resolve_module`, and any
+/// `ImportDeclaration::integrity` annotation checked against `cache::hash_source` of the
+/// resolved module via `cache::ImportCache::verify_integrity` before it's trusted.
+fn import_targets(_module: &ModuleRoot) -> Vec<(PathBuf, FileRange)> {
+    vec![]
+}
@@ -1,15 +1,19 @@
 use std::path::PathBuf;
 use program::Program;
-use passes::enumeration::enumerate_namespaces;
+use passes::enumeration::{enumerate_namespaces, NamespaceEnumerationOutput};
 use passes::name_clash::check_name_clashes;
 use passes::resolution::resolve_dependencies;
 use passes::typecheck::typecheck;
-use crate::core::DiagResult;
+use crate::core::{DiagResult, Diagnostic};
 
+pub mod cache;
+pub mod loader;
 mod namespace;
 mod passes;
 pub mod program;
+pub mod repl;
 pub mod resolver;
+mod suggest;
 
 /**
  * Top-level interface for semantic analysis.
@@ -64,27 +68,101 @@ pub mod resolver;
  * 
  * Once we are done with all passes, we output a Program instance that contains all errors and all modules (which contain all namespaces, which contain all declarations).
  */
-pub fn analyze(path: PathBuf) -> DiagResult<Program> {
+pub fn analyze(path: PathBuf, trace_parse: bool) -> DiagResult<Program> {
+    let (program, diagnostics) = analyze_staged(path, trace_parse);
+    DiagResult(program, diagnostics.all())
+}
+
+/// Like `analyze`, but keeps pass 1's diagnostics (parsing and namespace enumeration -
+/// structural) separate from passes 2-4's (resolution, typechecking, and name-clash checking -
+/// semantic), instead of concatenating everything into one `Vec`. A language server wants this
+/// split: structural diagnostics are cheap and meaningful even when the program doesn't fully
+/// resolve, so they can be reported the moment a file is re-parsed, while semantic diagnostics
+/// depend on cross-module resolution and are only worth recomputing - and reporting - once that
+/// finishes.
+pub fn analyze_staged(path: PathBuf, trace_parse: bool) -> (Option<Program>, AnalysisDiagnostics) {
     // Pass 1: Enumeration
-    let enumeration = enumerate_namespaces(path)?;
+    let DiagResult(enumeration, mut structural) = enumerate_namespaces(path, trace_parse);
+    let mut enumeration = match enumeration {
+        Some(enumeration) => enumeration,
+        // the entry point itself failed to resolve or parse - there is nothing to feed passes
+        // 2-4, so this program has no semantic diagnostics at all, not merely an empty set
+        None => return (None, AnalysisDiagnostics { structural, semantic: vec![] }),
+    };
+    // Pass 1's own diagnostics (parse failures, unresolved modules) travel inside the
+    // enumeration output; move them into the structural bucket so `resolve_and_check` only
+    // reports passes 2-4's here.
+    structural.append(&mut enumeration.diagnostics);
+    let DiagResult(program, semantic) = resolve_and_check(enumeration);
+    (program, AnalysisDiagnostics { structural, semantic })
+}
+
+/// Re-analyzes a program after the modules in `changed` have been edited, for a language server
+/// that would otherwise have to re-run the whole `analyze` pipeline on every keystroke.
+///
+/// This doesn't yet reuse `prev`'s registries the way its signature promises it eventually will:
+/// `Program` only keeps the finished namespace/declaration registries, not the
+/// `NamespaceEnumerationOutput` pass 1 built them from, and `handle_module` (enumeration.rs)
+/// doesn't walk a module's own imports/exports/declarations yet - the same gap `ReplSession`
+/// works around today by re-enumerating its whole buffer on every submitted line instead of
+/// just the new one. Until both are resolved there's no per-module state to selectively reuse,
+/// so this simply re-runs `analyze_staged` over everything reachable from `path`, ignoring
+/// `changed` - giving callers the split-diagnostics interface to code against now, with the
+/// performance benefit to follow once enumeration tracks per-module state.
+pub fn analyze_incremental(
+    _prev: &Program,
+    path: PathBuf,
+    _changed: &[PathBuf],
+    trace_parse: bool,
+) -> (Option<Program>, AnalysisDiagnostics) {
+    analyze_staged(path, trace_parse)
+}
+
+/// The diagnostics `analyze_staged` produces, split into the two buckets an incremental/LSP
+/// caller invalidates independently - see `analyze_staged`.
+pub struct AnalysisDiagnostics {
+    pub structural: Vec<Diagnostic>,
+    pub semantic: Vec<Diagnostic>,
+}
+
+impl AnalysisDiagnostics {
+    /// Every diagnostic from both buckets, structural first, in the same order `analyze`
+    /// has always reported them in.
+    pub fn all(&self) -> Vec<Diagnostic> {
+        self.structural.iter().cloned().chain(self.semantic.iter().cloned()).collect()
+    }
+}
+
+/// Passes 2-4 - resolution, typechecking, and name-clash checking - factored out of `analyze`
+/// so a caller that enumerates incrementally (the REPL's accumulating session in `repl`, and
+/// `analyze_staged`/`analyze_incremental` above) can re-run the rest of the pipeline over a
+/// fresh `NamespaceEnumerationOutput` without re-enumerating the whole program from scratch.
+pub(crate) fn resolve_and_check(enumeration: NamespaceEnumerationOutput) -> DiagResult<Program> {
     // Pass 2: Resolution
-    let resolution = resolve_dependencies(enumeration.modules, enumeration.declarations, enumeration.namespaces, enumeration.pureForwards);
+    let resolution = resolve_dependencies(
+        &enumeration.modules,
+        &enumeration.declarations,
+        enumeration.namespaces,
+        enumeration.pure_forwards,
+        enumeration.pure_imports,
+        enumeration.submodule_references,
+    );
     // Pass 3: Typechecking
-    let typechecked = typecheck(enumeration.declarations, resolution.namespaces);
+    let typechecked = typecheck(enumeration.declarations, &resolution.namespaces);
     // Pass 4: Name clashes
-    let nameClash = check_name_clashes(typechecked.declarations, resolution.namespaces);
+    let name_clash = check_name_clashes(typechecked.declarations, &resolution.namespaces);
     // Create program
     DiagResult(
         Some(Program::new(
             enumeration.modules,
             resolution.namespaces,
-            nameClash.declarations,
+            name_clash.declarations,
         )),
         vec![
             enumeration.diagnostics,
             resolution.diagnostics,
             typechecked.diagnostics,
-            nameClash.diagnostics
+            name_clash.diagnostics
         ].concat()
     )
 }
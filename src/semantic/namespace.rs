@@ -1,45 +1,77 @@
 use std::path::{Path, PathBuf};
 use std::collections::{hash_map::Entry, HashMap, VecDeque};
+use crate::core::{Applicability, Diagnostic, FileRange};
 use crate::syntax;
 use super::passes::dependencies::Dependency;
 
 
 // #region Namespaces
 
+/// Indexes `namespaces: Vec<Namespace>` in every pass downstream of enumeration. Namespaces
+/// live in one flat arena for the lifetime of the program being analyzed, addressed by this id
+/// rather than through nested persistent maps - resolution mutates a namespace's locals/exports
+/// in place through its id instead of rebuilding copies of the arena on every change.
+pub type NamespaceId = usize;
+
 /// A namespace is either a module or a declared namespace nested within another namespace.
 pub enum Namespace {
     /// A namespace declared within another namespace.
     /// This contains its parent's namespace id, and the corresponding id and syntax
     /// of its declaration.
     Nested {
-        namespace_id: usize,
-        parent_namespace_id: usize,
+        namespace_id: NamespaceId,
+        parent_namespace_id: NamespaceId,
         declaration_id: usize,
         // TODO: anonymous or regular
         node: syntax::NamespaceDeclaration,
-        /// Contains all references for all names locally-scoped to the namespace
-        locals: HashMap<&'static str, NameTarget>,
-        /// Contains all references for all of this namespace's exported names
-        exports: HashMap<&'static str, NameTarget>,
+        /// Whether this is a Cryptol-style `submodule X { .. }` rather than a plain `namespace
+        /// X { .. }`. Submodules additionally participate in the dependency-ordered resolution
+        /// `SubmoduleGraph` governs: an inner name like `B::y` must resolve `B` before its
+        /// members, so a submodule's own names are only resolved once every submodule its
+        /// instantiation references has already settled.
+        is_submodule: bool,
+        /// Contains all references for all names locally-scoped to the namespace, split by
+        /// `NameKind` so a type and a value can share a name without colliding
+        locals: HashMap<String, PerNs<NameTarget>>,
+        /// Contains all references for all of this namespace's exported names, split the same way
+        exports: HashMap<String, PerNs<NameTarget>>,
     },
     /// A module is a type of namespace, but has no parent, is not a declaration,
     /// and is alternatively identified by its file path.
     /// Other than that, it still has locals and exports just like any other namespace.
     /// Most namespaces in a program will be module namespaces.
     Module {
-        namespace_id: usize,
+        namespace_id: NamespaceId,
         absolute_path: PathBuf,
-        /// Contains all references for all names locally-scoped to the namespace
-        locals: HashMap<&'static str, NameTarget>,
-        /// Contains all references for all of this namespace's exported names
-        exports: HashMap<&'static str, NameTarget>,
+        /// Contains all references for all names locally-scoped to the namespace, split by
+        /// `NameKind` so a type and a value can share a name without colliding
+        locals: HashMap<String, PerNs<NameTarget>>,
+        /// Contains all references for all of this namespace's exported names, split the same way
+        exports: HashMap<String, PerNs<NameTarget>>,
     }
 }
 
 impl Namespace {
     pub fn new_nested(
-        namespace_id: usize,
-        parent_namespace_id: usize,
+        namespace_id: NamespaceId,
+        parent_namespace_id: NamespaceId,
+        declaration_id: usize,
+        node: syntax::NamespaceDeclaration
+    ) -> Namespace {
+        Namespace::Nested {
+            namespace_id,
+            parent_namespace_id,
+            declaration_id,
+            node,
+            is_submodule: false,
+            locals: HashMap::new(),
+            exports: HashMap::new(),
+        }
+    }
+
+    pub fn new_submodule(
+        namespace_id: NamespaceId,
+        parent_namespace_id: NamespaceId,
         declaration_id: usize,
         node: syntax::NamespaceDeclaration
     ) -> Namespace {
@@ -48,13 +80,24 @@ impl Namespace {
             parent_namespace_id,
             declaration_id,
             node,
+            is_submodule: true,
             locals: HashMap::new(),
             exports: HashMap::new(),
         }
     }
 
+    /// Whether this namespace is a `submodule X { .. }` rather than a plain `namespace`
+    /// declaration or a module. Only `Nested` namespaces can be submodules; a module is always
+    /// the root of its own tree.
+    pub fn is_submodule(&self) -> bool {
+        match self {
+            Namespace::Nested { is_submodule, .. } => *is_submodule,
+            Namespace::Module { .. } => false,
+        }
+    }
+
     pub fn new_module(
-        namespace_id: usize,
+        namespace_id: NamespaceId,
         absolute_path: PathBuf
     ) -> Namespace {
         Namespace::Module {
@@ -65,7 +108,7 @@ impl Namespace {
         }
     }
 
-    fn locals(&mut self) -> &mut HashMap<&'static str, NameTarget> {
+    fn locals(&mut self) -> &mut HashMap<String, PerNs<NameTarget>> {
         use Namespace::*;
 
         match self {
@@ -74,7 +117,7 @@ impl Namespace {
         }
     }
 
-    fn exports(&mut self) -> &mut HashMap<&'static str, NameTarget> {
+    fn exports(&mut self) -> &mut HashMap<String, PerNs<NameTarget>> {
         use Namespace::*;
 
         match self {
@@ -83,35 +126,90 @@ impl Namespace {
         }
     }
 
+    /// Every (name, `NameKind`) pair currently exported by this namespace - both kinds of every
+    /// exported name, even a kind nothing has touched yet, since a not-yet-touched kind is just
+    /// a blank `NameTarget` that will resolve to `NameTargetStatus::Empty` with no further work.
+    /// Used by the pure-forward expansion pass to discover which names a `* forward` needs to
+    /// re-export, without needing mutable access to (and therefore `or_insert`-ing a blank
+    /// target into) the exporter's own map.
+    pub fn export_names(&self) -> impl Iterator<Item = (String, NameKind)> + '_ {
+        use Namespace::*;
+
+        let exports = match self {
+            Nested { exports, .. } => exports,
+            Module { exports, .. } => exports,
+        };
+        exports.keys().flat_map(|name| [(name.clone(), NameKind::Type), (name.clone(), NameKind::Value)])
+    }
+
+    /// Every (name, `NameKind`) pair currently locally-scoped in this namespace. Used, alongside
+    /// `export_names`, to seed the worklist resolver with every target that might have
+    /// dependencies to chase.
+    pub fn local_names(&self) -> impl Iterator<Item = (String, NameKind)> + '_ {
+        use Namespace::*;
+
+        let locals = match self {
+            Nested { locals, .. } => locals,
+            Module { locals, .. } => locals,
+        };
+        locals.keys().flat_map(|name| [(name.clone(), NameKind::Type), (name.clone(), NameKind::Value)])
+    }
+
+    /// Read-only peek at an export target in the given namespace kind, without `or_insert`-ing
+    /// a blank one if absent. Used by the worklist resolver to ask "does this namespace export
+    /// this name, as this kind" without side effects.
+    pub fn export_target(&self, name: &str, kind: NameKind) -> Option<&NameTarget> {
+        use Namespace::*;
+
+        match self {
+            Nested { exports, .. } => exports.get(name),
+            Module { exports, .. } => exports.get(name),
+        }.map(|per_ns| per_ns.get(kind))
+    }
+
+    /// Read-only peek at a local target in the given namespace kind; see `export_target`.
+    pub fn local_target(&self, name: &str, kind: NameKind) -> Option<&NameTarget> {
+        use Namespace::*;
+
+        match self {
+            Nested { locals, .. } => locals.get(name),
+            Module { locals, .. } => locals.get(name),
+        }.map(|per_ns| per_ns.get(kind))
+    }
+
     // #region helpers
 
-    /// Gets the NameTarget corresponding to a local.
+    /// Gets the NameTarget corresponding to a local in the given namespace kind.
     /// This will insert a new `NameTarget` if one was not present.
     /// Use `local_target_entry()` if this is not desired.
-    pub fn get_local_target(&mut self, name: &'static str) -> &mut NameTarget {
-        self.local_target_entry(name).or_insert_with(|| { NameTarget::new() })
+    pub fn get_local_target(&mut self, name: &str, kind: NameKind) -> &mut NameTarget {
+        self.local_target_entry(name).or_insert_with(PerNs::blank).get_mut(kind)
     }
 
-    /// Gets the NameTarget corresponding to an export.
+    /// Gets the NameTarget corresponding to an export in the given namespace kind.
     /// This will insert a new `NameTarget` if one was not present.
-    /// Use `local_target_entry()` if this is not desired.
-    pub fn get_export_target(&mut self, name: &'static str) -> &mut NameTarget {
-        self.export_target_entry(name).or_insert_with(|| { NameTarget::new() })
+    /// Use `export_target_entry()` if this is not desired.
+    pub fn get_export_target(&mut self, name: &str, kind: NameKind) -> &mut NameTarget {
+        self.export_target_entry(name).or_insert_with(PerNs::blank).get_mut(kind)
     }
 
-    /// Gets the map entry corresponding to a local.
-    pub fn local_target_entry(&mut self, name: &'static str) -> Entry<&'static str, NameTarget> {
-        self.locals().entry(name)
+    /// Gets the map entry corresponding to a local, across both of its namespace kinds at once.
+    pub fn local_target_entry(&mut self, name: &str) -> Entry<String, PerNs<NameTarget>> {
+        self.locals().entry(name.to_string())
     }
 
-    /// Gets the map entry corresponding to an export.
-    pub fn export_target_entry(&mut self, name: &'static str) -> Entry<&'static str, NameTarget> {
-        self.exports().entry(name)
+    /// Gets the map entry corresponding to an export, across both of its namespace kinds at once.
+    pub fn export_target_entry(&mut self, name: &str) -> Entry<String, PerNs<NameTarget>> {
+        self.exports().entry(name.to_string())
     }
 
     // #endregion
     // #region local references
 
+    /// An imported name's kind isn't known until its supplying module's own declaration
+    /// resolves, so (like rustc_resolve importing a single-name `use`) the reference is
+    /// registered speculatively into both namespace kinds; whichever kind the source module
+    /// never actually populates under this name simply resolves to an empty target.
     pub fn add_imported_name(
         &mut self,
         name: String,
@@ -119,55 +217,112 @@ impl Namespace {
         export_name: String,
         declaration_id: usize
     ) {
-        self.get_local_target(&name).add_reference(Reference::RemoteName {
-            module_path,
-            export_name,
-            resolved_declaration_id: declaration_id
-        });
+        for kind in [NameKind::Type, NameKind::Value] {
+            self.get_local_target(&name, kind).add_reference(Reference::RemoteName {
+                module_path,
+                export_name: export_name.clone(),
+                resolved_declaration_id: declaration_id
+            });
+        }
     }
 
     pub fn add_imported_namespace(&mut self, name: String, module_path: &'static Path, declaration_id: usize) {
-        self.get_local_target(&name).add_reference(Reference::RemoteNamespace {
-            module_path,
-            resolved_declaration_id: declaration_id
-        });
+        for kind in [NameKind::Type, NameKind::Value] {
+            self.get_local_target(&name, kind).add_reference(Reference::RemoteNamespace {
+                module_path,
+                resolved_declaration_id: declaration_id
+            });
+        }
     }
 
-    pub fn add_local_declaration(&mut self, name: String, declaration_id: usize) {
-        self.get_local_target(&name).add_reference(Reference::LocalDeclaration {
-            resolved_declaration_id: declaration_id
+    /// Registers a direct declaration (function, type, constant, or namespace) under `name`
+    /// in this namespace's locals, in the namespace kind `kind` implies (see `Declaration::kinds`
+    /// for which kind(s) each declaration variant occupies - a namespace declaration is
+    /// registered under both, one call per kind). If `name` already has a direct declaration
+    /// registered here under this same kind, `policy` decides what happens: `Forbid` (the
+    /// default for named functions, types, and constants - see `Declaration::merge_policy`)
+    /// reports a redefinition diagnostic naming both declarations and where each was written;
+    /// `Allow` (namespace re-openings, eventually anonymous declarations) lets the merge
+    /// through silently. Either way the new reference is still added, so later passes merge
+    /// both declarations rather than dropping one silently even when `Forbid` fires. This only
+    /// ever compares against references already present on *this* namespace's own local target
+    /// for this kind, so the same name declared in two different namespaces - or as a type in
+    /// one and a value in the other - never conflicts.
+    pub fn add_local_declaration(&mut self, name: String, kind: NameKind, declaration_id: usize, location: FileRange, policy: MergePolicy) -> Option<Diagnostic> {
+        let target = self.get_local_target(&name, kind);
+        let diagnostic = if policy == MergePolicy::Forbid {
+            target.own_declaration().map(|(existing_id, existing_location)| {
+                duplicate_declaration_diagnostic("declaration", &name, existing_id, existing_location, declaration_id, &location)
+            })
+        } else {
+            None
+        };
+        target.add_reference(Reference::LocalDeclaration {
+            resolved_declaration_id: declaration_id,
+            location,
         });
+        diagnostic
     }
 
     // #endregion
     // #region export references
 
+    /// See `add_imported_name` - the same speculative both-kinds registration applies here.
     pub fn add_forwarded_name(&mut self, name: String, module_path: &'static Path, export_name: String, declaration_id: usize) {
-        self.get_export_target(&name).add_reference(Reference::RemoteName {
-            module_path,
-            export_name,
-            resolved_declaration_id: declaration_id
-        });
+        for kind in [NameKind::Type, NameKind::Value] {
+            self.get_export_target(&name, kind).add_reference(Reference::RemoteName {
+                module_path,
+                export_name: export_name.clone(),
+                resolved_declaration_id: declaration_id
+            });
+        }
     }
 
     pub fn add_exported_remote_namespace(&mut self, name: String, module_path: &'static Path, declaration_id: usize) {
-        self.get_export_target(&name).add_reference(Reference::RemoteNamespace {
-            module_path,
-            resolved_declaration_id: declaration_id
-        });
+        for kind in [NameKind::Type, NameKind::Value] {
+            self.get_export_target(&name, kind).add_reference(Reference::RemoteNamespace {
+                module_path,
+                resolved_declaration_id: declaration_id
+            });
+        }
     }
 
-    pub fn add_exported_name(&mut self, name: String, local: String, declaration_id: usize) {
-        self.get_export_target(&name).add_reference(Reference::LocalName {
-            name: local,
-            resolved_declaration_id: declaration_id
-        });
+    /// Registers an exported name as an alias of a local declaration. Like `add_imported_name`,
+    /// which kind(s) of the local the alias actually reaches isn't known until the local itself
+    /// resolves, so both are registered speculatively. Like `add_local_declaration`, a second
+    /// export under the same `name` and kind in this namespace is flagged as a redefinition,
+    /// scoped to this namespace's own export target only.
+    pub fn add_exported_name(&mut self, name: String, local: String, declaration_id: usize, location: FileRange) -> Option<Diagnostic> {
+        let mut diagnostic = None;
+        for kind in [NameKind::Type, NameKind::Value] {
+            let target = self.get_export_target(&name, kind);
+            if diagnostic.is_none() && target.has_own_declaration() {
+                diagnostic = Some(Diagnostic::new(format!("Duplicate export of \"{}\" in this module", name), location.clone()));
+            }
+            target.add_reference(Reference::LocalName {
+                name: local.clone(),
+                resolved_declaration_id: declaration_id
+            });
+        }
+        diagnostic
     }
 
-    pub fn add_exported_declaration(&mut self, name: String, declaration_id: usize) {
-        self.get_export_target(&name).add_reference(Reference::LocalDeclaration {
-            resolved_declaration_id: declaration_id
+    /// Same merge-policy gating as `add_local_declaration`, applied to an exported declaration
+    /// instead of a local one - see there for what `policy` does and who defaults to which.
+    pub fn add_exported_declaration(&mut self, name: String, kind: NameKind, declaration_id: usize, location: FileRange, policy: MergePolicy) -> Option<Diagnostic> {
+        let target = self.get_export_target(&name, kind);
+        let diagnostic = if policy == MergePolicy::Forbid {
+            target.own_declaration().map(|(existing_id, existing_location)| {
+                duplicate_declaration_diagnostic("export", &name, existing_id, existing_location, declaration_id, &location)
+            })
+        } else {
+            None
+        };
+        target.add_reference(Reference::LocalDeclaration {
+            resolved_declaration_id: declaration_id,
+            location,
         });
+        diagnostic
     }
 
     // #endregion
@@ -185,7 +340,7 @@ pub enum ModuleRef {
     NotFound { fullyResolved: bool },
     /// The module was found and parsed
     Success {
-        namespaceId: usize,
+        namespaceId: NamespaceId,
         fullyResolved: bool,
     }
 }
@@ -236,6 +391,7 @@ pub enum ModuleRef {
 ///   with circular references.
 pub struct NameTarget {
     status: NameTargetStatus,
+    determinacy: Determinacy,
     references: Vec<Reference>,
     dependencies: VecDeque<Dependency>,
 }
@@ -244,24 +400,139 @@ impl NameTarget {
     pub fn new() -> NameTarget {
         NameTarget {
             status: NameTargetStatus::NotResolved,
+            determinacy: Determinacy::Undetermined,
             references: vec![],
             dependencies: VecDeque::new(),
         }
     }
 
-    fn add_reference(&mut self, reference: Reference) {
+    pub fn add_reference(&mut self, reference: Reference) {
         self.references.push(reference);
     }
 
-    fn add_dependency(&mut self, dep: Dependency) {
+    pub fn determinacy(&self) -> Determinacy {
+        self.determinacy
+    }
+
+    /// Whether the worklist resolver has already committed this target's final reference set
+    /// - the `Determinacy::Determined` case of `determinacy()`. Once true, this target is done:
+    /// nothing will revisit it again, whether it ended up `FullyResolved`, `Dangling`,
+    /// `Circular`, or `Ambiguous`. A caller deciding whether to requeue a target for another
+    /// pass should check this rather than re-deriving the same answer from
+    /// `has_pending_dependencies` - `determine()` is the single place that flips it, exactly
+    /// when a target's dependency queue has fully drained.
+    pub fn is_determined(&self) -> bool {
+        self.determinacy == Determinacy::Determined
+    }
+
+    /// Whether this target still has at least one dependency the worklist resolver hasn't
+    /// yet converted into a reference (successful or otherwise).
+    pub fn has_pending_dependencies(&self) -> bool {
+        !self.dependencies.is_empty()
+    }
+
+    pub fn is_ambiguous(&self) -> bool {
+        self.status == NameTargetStatus::Ambiguous
+    }
+
+    /// Whether this target's aggregate status has settled on `Circular`. Used by namespace
+    /// member lookups (`ns.foo`) to tell a member that's merely missing from a member whose
+    /// own dependency chain loops back on itself, since the two are reported differently.
+    pub fn is_circular(&self) -> bool {
+        self.status == NameTargetStatus::Circular
+    }
+
+    /// The declaration a resolved reference on this target ultimately points at, if any.
+    /// Declaration merging means a target can carry more than one resolved reference; a
+    /// caller that just needs "a" declaration id to forward along takes the first, matching
+    /// how `add_reference` always appends rather than replaces.
+    pub fn resolved_declaration_id(&self) -> Option<usize> {
+        self.references.iter().find_map(|r| r.resolved_declaration_id())
+    }
+
+    /// Commits this target's current reference set as final. Called once the worklist
+    /// resolver has either converted every dependency this target ever had into a
+    /// reference, or given up on whatever's left as circular - either way, nothing will
+    /// revisit this target again, so its aggregate status is locked in now.
+    pub fn determine(&mut self) {
+        self.set_aggregate_status();
+        self.determinacy = Determinacy::Determined;
+    }
+
+    /// Whether this target already has a direct (non-imported) declaration registered.
+    /// Used to detect redefinitions: a name can legitimately gain remote or forwarded
+    /// references over time, but two local declarations under the same name is an error.
+    fn has_own_declaration(&self) -> bool {
+        self.references.iter().any(|r| matches!(r, Reference::LocalDeclaration { .. } | Reference::LocalName { .. }))
+    }
+
+    /// The first direct declaration already registered on this target, if any - its
+    /// declaration id and the location it was written at. Used by `add_local_declaration`/
+    /// `add_exported_declaration` to build a diagnostic that points at both the existing
+    /// declaration and whatever new one is about to collide with it.
+    fn own_declaration(&self) -> Option<(usize, &FileRange)> {
+        self.references.iter().find_map(|r| match r {
+            Reference::LocalDeclaration { resolved_declaration_id, location } => Some((*resolved_declaration_id, location)),
+            _ => None,
+        })
+    }
+
+    /// Registers a dependency that can resolve `name` for this target, borrowing ECMAScript's
+    /// star-export resolution rule. If an earlier dependency already registered here names a
+    /// *different* supplying module than `dep`, the two suppliers can never be told apart from
+    /// just their module paths, so the name is ambiguous: this target is marked
+    /// `NameTargetStatus::Ambiguous` and a diagnostic is returned for it. The new dependency is
+    /// still recorded regardless, so a later, explicit declaration of the same name can still
+    /// be detected as a redefinition. An explicit binding always wins over any number of star
+    /// contributions, though: once this target already has its own declaration (`has_own_declaration`),
+    /// additional suppliers merely shadowed by it are never flagged as ambiguous. `ExportedName`
+    /// dependencies (an alias of a declaration local to this module) never collide with
+    /// anything, since they don't name a supplying module at all - this is how a name that
+    /// merely shadows within the same module stays distinct from one forwarded in from
+    /// elsewhere. This is shared by both locals (imports) and exports (forwards): whichever
+    /// map a combinator's dependency ends up in, the same rule applies.
+    pub fn add_dependency(&mut self, name: &str, dep: Dependency) -> Option<Diagnostic> {
+        let diagnostic = if self.has_own_declaration() {
+            None
+        } else {
+            dep.export_module().and_then(|incoming_module| {
+                self.dependencies.iter()
+                    .find_map(|existing| existing.export_module().filter(|m| *m != incoming_module))
+                    .map(|existing_module| {
+                        self.status = NameTargetStatus::Ambiguous;
+                        Diagnostic::new(
+                            format!(
+                                "\"{}\" is ambiguous (supplied by both \"{}\" and \"{}\")",
+                                name, existing_module, incoming_module
+                            ),
+                            dep.star_location().unwrap_or_else(|| dep.export_module_location().unwrap()).clone()
+                        )
+                    })
+            })
+        };
         self.dependencies.push_back(dep);
+        diagnostic
     }
 
-    fn pop_dependency(&mut self) -> Option<Dependency> {
+    /// Removes and returns the next not-yet-attempted dependency, if any remain.
+    pub fn pop_dependency(&mut self) -> Option<Dependency> {
         self.dependencies.pop_front()
     }
 
+    /// How many dependencies are currently queued. Used by the worklist resolver to drain
+    /// exactly the dependencies queued at the start of a pass, without looping forever on
+    /// ones it re-queues for a later pass.
+    pub fn dependency_count(&self) -> usize {
+        self.dependencies.len()
+    }
+
     fn set_aggregate_status(&mut self) {
+        if self.status == NameTargetStatus::Ambiguous {
+            // `add_dependency` already determined this name can never be told apart from its
+            // colliding supplier; that verdict doesn't depend on how any individual reference
+            // ended up resolving, so it's left standing rather than recomputed here.
+            return;
+        }
         if self.references.iter().any(|r| { r.status() == NameTargetStatus::FullyResolved }) {
             self.status = NameTargetStatus::FullyResolved;
         } else if self.references.iter().any(|r| { r.status() == NameTargetStatus::Dangling }) {
@@ -286,6 +557,82 @@ pub enum NameTargetStatus {
     Circular,
     /// All references could be resolved, but none contain a declaration
     Empty,
+    /// Two or more distinct suppliers contributed this name and neither is shadowed by an
+    /// explicit local/named declaration; the name is excluded from the namespace's exports
+    /// rather than arbitrarily picking a winner
+    Ambiguous,
+}
+
+/// Whether a `NameTarget`'s final reference set might still change.
+///
+/// This is the axis the worklist resolver actually drives, and it's deliberately kept
+/// separate from `NameTargetStatus`: status describes *what a target resolved to*, while
+/// determinacy describes *whether resolution is still in progress*. A target can gain new
+/// dependencies mid-resolution (a `PureForwardReplacement` expansion can introduce a name
+/// that wasn't known about up front), so "not yet looked at" and "looked at and gave up"
+/// have to stay distinct from "done" - conflating them is exactly what made the old
+/// recursive reduce-with-a-chain approach unable to let a still-expanding forward feed back
+/// into a pending import.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Determinacy {
+    /// This target might still gain or lose references as other targets resolve.
+    Undetermined,
+    /// This target's reference set is final; nothing will revisit it again.
+    Determined,
+}
+
+/// Which of a name's independent namespaces a lookup or declaration belongs to, borrowing
+/// rustc_resolve's type/value split: `Map<K, V>` resolves `Map` in `Type`, while `map(xs)`
+/// resolves `map` in `Value`, so a struct and a function can share a name without either one
+/// shadowing the other. See `PerNs` for where this actually splits a name's storage, and
+/// `Declaration::kinds` for which kind(s) each declaration variant occupies.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameKind {
+    Type,
+    Value,
+}
+
+/// Whether a second direct declaration under a name already occupied by one is a genuine
+/// collision or an allowed merge, borrowing rustc_resolve's per-declaration duplicate-checking
+/// mode. See `Declaration::merge_policy` for the default this maps each declaration variant to,
+/// and `Namespace::add_local_declaration`/`add_exported_declaration` for where it's applied.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// A second declaration under this name is reported as a duplicate definition.
+    Forbid,
+    /// A second declaration under this name merges silently with the one already there.
+    Allow,
+}
+
+/// Splits a name's resolution state into independent type and value slots, borrowing
+/// rustc_resolve's `PerNS` type. Both slots always exist - even a kind nothing has declared
+/// under this name is just a `NameTarget` with no references, which resolves to
+/// `NameTargetStatus::Empty` on its own rather than needing special-casing as "absent".
+pub struct PerNs<T> {
+    pub type_ns: T,
+    pub value_ns: T,
+}
+
+impl<T> PerNs<T> {
+    pub fn get(&self, kind: NameKind) -> &T {
+        match kind {
+            NameKind::Type => &self.type_ns,
+            NameKind::Value => &self.value_ns,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: NameKind) -> &mut T {
+        match kind {
+            NameKind::Type => &mut self.type_ns,
+            NameKind::Value => &mut self.value_ns,
+        }
+    }
+}
+
+impl PerNs<NameTarget> {
+    fn blank() -> PerNs<NameTarget> {
+        PerNs { type_ns: NameTarget::new(), value_ns: NameTarget::new() }
+    }
 }
 
 pub enum Reference {
@@ -305,9 +652,12 @@ pub enum Reference {
         name: String,
         resolved_declaration_id: usize,
     },
-    /// A direct reference to a local declaration.
+    /// A direct reference to a local declaration. `location` is where that declaration was
+    /// written, so a second one under the same name can build a duplicate-definition
+    /// diagnostic that points at both sites instead of just the new one.
     LocalDeclaration {
         resolved_declaration_id: usize,
+        location: FileRange,
     },
     /// A reference to a module that doesn't exist.
     /// This applies for both named and wildcard imports/forwards.
@@ -364,6 +714,21 @@ impl Reference {
             _ => NameTargetStatus::Empty
         }
     }
+
+    /// The declaration this reference points at, for the four resolved reference kinds.
+    /// The dangling and circular kinds never carry one - that's exactly what makes them
+    /// dangling or circular.
+    fn resolved_declaration_id(&self) -> Option<usize> {
+        use Reference::*;
+
+        match self {
+            RemoteName { resolved_declaration_id, .. }
+            | RemoteNamespace { resolved_declaration_id, .. }
+            | LocalName { resolved_declaration_id, .. }
+            | LocalDeclaration { resolved_declaration_id, .. } => Some(*resolved_declaration_id),
+            _ => None,
+        }
+    }
 }
 
 /// A semantic declaration is a node that is ultimately associated with a name
@@ -388,6 +753,67 @@ pub enum Declaration {
     },
     Namespace {
         declaration_id: usize,
-        namespace_id: usize,
+        namespace_id: NamespaceId,
+    }
+}
+
+impl Declaration {
+    /// Which namespace kind(s) this declaration occupies, for routing into `Namespace::
+    /// add_local_declaration`/`add_exported_declaration`. A function or constant is a value; a
+    /// type is, unsurprisingly, a type. A namespace is the one variant that occupies both - it
+    /// can be named in a type position the same way an `import * as X` binding can (`X::SomeType`),
+    /// while also serving as a value-position path prefix for a function or constant it exports
+    /// (`X::someConstant`) - so enumerating its declaration registers it under both kinds.
+    pub fn kinds(&self) -> &'static [NameKind] {
+        use Declaration::*;
+
+        match self {
+            Function { .. } | Constant { .. } => &[NameKind::Value],
+            Type { .. } => &[NameKind::Type],
+            Namespace { .. } => &[NameKind::Type, NameKind::Value],
+        }
     }
+
+    /// The default merge policy for a second declaration under this one's name, for routing
+    /// into `Namespace::add_local_declaration`/`add_exported_declaration`. A namespace is
+    /// always mergeable - re-opening `namespace X { .. }` a second time is how a namespace
+    /// gains more members in the first place, not a redefinition - so two `Namespace`
+    /// declarations under the same name always merge silently. A function, type, or constant
+    /// forbids a second declaration under its name outright: two of those sharing a name in
+    /// the same scope is always a genuine collision, not a merge.
+    ///
+    /// TODO: once the anonymous-or-regular distinction on `Function`/`Type`/`Constant` (see the
+    /// TODOs above) is wired up, an anonymous declaration should report `Allow` here instead -
+    /// it was never meant to be found by name, so a name collision on it isn't user-visible.
+    pub fn merge_policy(&self) -> MergePolicy {
+        match self {
+            Declaration::Namespace { .. } => MergePolicy::Allow,
+            Declaration::Function { .. } | Declaration::Type { .. } | Declaration::Constant { .. } => MergePolicy::Forbid,
+        }
+    }
+}
+
+/// Shared by `add_local_declaration` and `add_exported_declaration`: builds a diagnostic
+/// naming both the existing declaration a new one collides with and the new one itself, each
+/// with its own id and the location it was written at. Carries a `MaybeIncorrect` suggestion
+/// that deletes the colliding declaration's own text - correct whenever that text is nothing
+/// but the duplicate, but left at `MaybeIncorrect` rather than `MachineApplicable` since a
+/// `--fix` run can't tell that from a declaration that also does something else the surrounding
+/// code still depends on.
+fn duplicate_declaration_diagnostic(
+    noun: &str,
+    name: &str,
+    existing_id: usize,
+    existing_location: &FileRange,
+    new_id: usize,
+    new_location: &FileRange,
+) -> Diagnostic {
+    let (line, column) = existing_location.start();
+    Diagnostic::new(
+        format!(
+            "Duplicate {} of \"{}\": declaration #{} conflicts with the one already declared at {}:{} (#{})",
+            noun, name, new_id, line, column, existing_id
+        ),
+        new_location.clone()
+    ).with_suggestion(new_location.clone(), String::new(), Applicability::MaybeIncorrect)
 }
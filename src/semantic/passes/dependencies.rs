@@ -1,6 +1,6 @@
 #![feature(option_expect_none)]
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use crate::core::FileRange;
 use crate::parser::lexer::Token;
 
@@ -12,6 +12,48 @@ pub struct PureForward {
     starLocation: FileRange
 }
 
+impl PureForward {
+    pub fn new(forwardNamespace: u32, exportModule: String, exportModuleLocation: FileRange, starLocation: FileRange) -> PureForward {
+        PureForward { forwardNamespace, exportModule, exportModuleLocation, starLocation }
+    }
+
+    pub fn forward_namespace(&self) -> u32 { self.forwardNamespace }
+
+    pub fn export_module(&self) -> &str { &self.exportModule }
+
+    pub fn export_module_location(&self) -> &FileRange { &self.exportModuleLocation }
+
+    pub fn star_location(&self) -> &FileRange { &self.starLocation }
+}
+
+/// A glob import (`import from "mod" : *`), registered at enumeration time and expanded, once
+/// resolution starts, into one `PureImportReplacement` dependency per name `mod` currently
+/// exports - mirroring `PureForward`, but landing on the importing namespace's *locals* instead
+/// of another namespace's exports. Unlike a pure forward, this never needs cycle detection:
+/// nothing downstream of resolution ever forwards from another namespace's locals, so a chain
+/// of glob imports can't loop back on itself the way a chain of `export * from` forwards can.
+#[derive(Clone, Debug)]
+pub struct PureImport {
+    importNamespace: u32,
+    exportModule: String,
+    exportModuleLocation: FileRange,
+    starLocation: FileRange
+}
+
+impl PureImport {
+    pub fn new(importNamespace: u32, exportModule: String, exportModuleLocation: FileRange, starLocation: FileRange) -> PureImport {
+        PureImport { importNamespace, exportModule, exportModuleLocation, starLocation }
+    }
+
+    pub fn import_namespace(&self) -> u32 { self.importNamespace }
+
+    pub fn export_module(&self) -> &str { &self.exportModule }
+
+    pub fn export_module_location(&self) -> &FileRange { &self.exportModuleLocation }
+
+    pub fn star_location(&self) -> &FileRange { &self.starLocation }
+}
+
 pub enum Dependency {
     ImportedName {
         importNamespace: u32,
@@ -45,6 +87,17 @@ pub enum Dependency {
         exportModuleLocation: FileRange,
         starLocation: FileRange
     },
+    /// A glob import is processed and replaced with ad hoc imported names, for the same reason
+    /// a `PureForward` is: the name itself is pulled from the exporting module's exports rather
+    /// than written out at the import site, and any errors are anchored to the original glob's
+    /// star location.
+    PureImportReplacement {
+        importNamespace: u32,
+        importName: String,
+        exportModule: String,
+        exportModuleLocation: FileRange,
+        starLocation: FileRange
+    },
     ForwardedNamespace {
         forwardNamespace: u32,
         forwardName: Token,
@@ -59,6 +112,69 @@ pub enum Dependency {
     }
 }
 
+impl Dependency {
+    /// The module this dependency's name ultimately traces back to, if it names one.
+    /// `ExportedName` aliases a local declaration rather than another module, so it has no
+    /// supplying module and can never participate in a cross-module name collision.
+    pub fn export_module(&self) -> Option<&str> {
+        use Dependency::*;
+
+        match self {
+            ImportedName { exportModule, .. } | ImportedNamespace { exportModule, .. }
+            | ForwardedName { exportModule, .. } | ForwardedNamespace { exportModule, .. }
+            | PureForwardReplacement { exportModule, .. } | PureImportReplacement { exportModule, .. } => Some(exportModule),
+            ExportedName { .. } => None,
+        }
+    }
+
+    /// The location of the module path literal this dependency's name was imported/forwarded from.
+    pub fn export_module_location(&self) -> Option<&FileRange> {
+        use Dependency::*;
+
+        match self {
+            ImportedName { exportModuleLocation, .. } | ImportedNamespace { exportModuleLocation, .. }
+            | ForwardedName { exportModuleLocation, .. } | ForwardedNamespace { exportModuleLocation, .. }
+            | PureForwardReplacement { exportModuleLocation, .. } | PureImportReplacement { exportModuleLocation, .. } => Some(exportModuleLocation),
+            ExportedName { .. } => None,
+        }
+    }
+
+    /// The location of the `*` token that brought this name in, for the wildcard/forward forms
+    /// where no single name was written out at the import/forward site.
+    pub fn star_location(&self) -> Option<&FileRange> {
+        use Dependency::*;
+
+        match self {
+            ImportedNamespace { starLocation, .. } | ForwardedNamespace { starLocation, .. }
+            | PureForwardReplacement { starLocation, .. } | PureImportReplacement { starLocation, .. } => Some(starLocation),
+            _ => None,
+        }
+    }
+}
+
+/// One submodule declaration referencing another by name - either `submodule A = F X` (`A`'s
+/// instantiation needs `X` resolved first) or `import submodule X` written inside another
+/// submodule's body. Fed into `SubmoduleGraph`, mirroring how a `PureForward` is fed into
+/// `PureForwardGraph`.
+#[derive(Clone, Debug)]
+pub struct SubmoduleReference {
+    referencer: u32,
+    referenced: u32,
+    location: FileRange,
+}
+
+impl SubmoduleReference {
+    pub fn new(referencer: u32, referenced: u32, location: FileRange) -> SubmoduleReference {
+        SubmoduleReference { referencer, referenced, location }
+    }
+
+    pub fn referencer(&self) -> u32 { self.referencer }
+
+    pub fn referenced(&self) -> u32 { self.referenced }
+
+    pub fn location(&self) -> &FileRange { &self.location }
+}
+
 pub struct PureForwardGraph {
     map: HashMap<(usize, usize), PureForward>,
     size: usize,
@@ -100,68 +216,138 @@ impl PureForwardGraph {
     /// No node will appear in more than one cycle;
     /// any cycles that intersect will be merged into one "aggregate" cycle.
     pub fn get_cycles(&self) -> HashMap<usize, HashSet<usize>> {
-        // visit
-        let cycles: Vec<HashSet<usize>> = vec![];
-        self.cycles_visitor(0, &mut vec![], &mut HashSet::new(), &mut cycles);
-        // assemble map
-        let map = HashMap::new();
-        for cycle in cycles {
-            for ns in cycle { map.insert(ns, cycle); }
+        find_cycles(self.size, &|ns| self.get_consumers(ns))
+    }
+}
+
+/// Edges among submodule declarations: an edge from `referenced` to `referencer` means
+/// "`referencer`'s instantiation needs `referenced` resolved first" (`submodule A = F X`, or
+/// `import submodule X` written inside another submodule) - the same shape `PureForwardGraph`
+/// uses for "B forwards everything from A", so cycle detection (`find_cycles`, below) is shared
+/// between the two. What a cycle *means* differs: a pure-forward cycle is a diagnosed but legal
+/// situation (each forward in it is reported and left unexpanded, so it exposes no names), while submodules form a
+/// tree by construction - a cycle here (Cryptol-style: `submodule A = F X` where `F` imports
+/// `A`) can never be linearized and is always an error.
+pub struct SubmoduleGraph {
+    map: HashMap<(usize, usize), FileRange>,
+    size: usize,
+}
+
+impl SubmoduleGraph {
+    pub fn new(size: usize) -> Self {
+        SubmoduleGraph { map: HashMap::new(), size }
+    }
+
+    pub fn add_reference(&mut self, reference: &SubmoduleReference) {
+        self.map.insert((reference.referenced() as usize, reference.referencer() as usize), reference.location().clone());
+    }
+
+    /// Get all submodules whose instantiation references this one.
+    pub fn get_consumers(&self, referenced: usize) -> Vec<usize> {
+        self.map.keys()
+            .filter(|key| key.0 == referenced)
+            .map(|key| key.1)
+            .collect()
+    }
+
+    /// The location of the reference from `referenced` to `referencer`, if one was recorded -
+    /// used to anchor the diagnostic when a cycle through these two is reported.
+    pub fn get_location(&self, referenced: usize, referencer: usize) -> Option<&FileRange> {
+        self.map.get(&(referenced, referencer))
+    }
+
+    /// Determines the illegal submodule-instantiation cycles in this graph, using the same
+    /// search `PureForwardGraph::get_cycles` does.
+    pub fn get_cycles(&self) -> HashMap<usize, HashSet<usize>> {
+        find_cycles(self.size, &|ns| self.get_consumers(ns))
+    }
+
+    /// Orders every submodule so each comes after every submodule its instantiation
+    /// references, via Kahn's algorithm over the same edges `get_cycles` walks. Returns `None`
+    /// if the graph isn't a DAG - callers are expected to have already checked `get_cycles` and
+    /// diagnosed the offending namespaces before ever reaching here.
+    pub fn topological_order(&self) -> Option<Vec<usize>> {
+        let mut in_degree = vec![0usize; self.size];
+        for &(_referenced, referencer) in self.map.keys() {
+            in_degree[referencer] += 1;
         }
-        map
-    }
-
-    /// Performs a recursive aggregate cycles algorithm for a starting namespace, given the current recursion path,
-    /// set of visited namespaces, and current set of cycles.
-    /// 
-    /// For each consumer of the namespace, check to see if it exists in the current recursion path.
-    /// If it does, the path between the two namespaces either needs to be merged into an existing cycle
-    /// or added as a new cycle.
-    /// This is a depth-first search algorithm.
-    /// Once every consumer of the namespace is visited, the namespace is marked visited and the algorithm will
-    /// ascend back to the previous namespace.
-    /// If there are no namespaces left in the chain, the algorithm moves to the next namespace in the graph and starts
-    /// a new chain.
-    /// The algorithm is finished once every namespace in the graph has been visited, either by recursion from
-    /// an existing namespace or by iteration.
-    fn cycles_visitor(
-        &self,
-        ns: usize,
-        current_path: &mut Vec<usize>,
-        visited: &mut HashSet<usize>,
-        cycles: &mut Vec<HashSet<usize>>
-    ) {
-        // break recursion if we're out of namespaces or the namespace has already been visited
-        if ns >= self.size || visited.contains(&ns) { return; }
-
-        current_path.push(ns);
-        for consumer in self.get_consumers(ns) {
-            if consumer == ns {
-                // TODO: figure out how to get diagnostics here (possibly just add another method to check for this)
-                // for posterity: if this is true, the namespace has a pure forward to itself, which should be just a warning
-            } else if let Some(consumer_index) = current_path.iter().position(|&n| n == consumer) {
-                // we have a cycle, gather all namespaces in the path
-                let cycle = &current_path[consumer_index..];
-                // check to see if there is an existing cycle containing ANY of them
-                if let Some(existing_cycle) = cycles.iter().find(|c| cycle.iter().any(|n| c.contains(n))) {
-                    // existing cycle, merge all of these into it
-                    existing_cycle.extend(cycle);
-                } else {
-                    // no existing cycle, add one
-                    cycles.push(cycle.iter().cloned().collect());
-                }
-            } else {
-                // no cycle, recurse to consumer
-                self.cycles_visitor(consumer, current_path, visited, cycles);
+        let mut ready: VecDeque<usize> = (0..self.size).filter(|&ns| in_degree[ns] == 0).collect();
+        let mut order = Vec::with_capacity(self.size);
+        while let Some(ns) = ready.pop_front() {
+            order.push(ns);
+            for consumer in self.get_consumers(ns) {
+                in_degree[consumer] -= 1;
+                if in_degree[consumer] == 0 { ready.push_back(consumer); }
             }
         }
+        if order.len() == self.size { Some(order) } else { None }
+    }
+}
+
+/// Depth-first cycle search shared by `PureForwardGraph` and `SubmoduleGraph`: finds every
+/// cycle among `size` nodes, given a callback returning a node's direct consumers. No node
+/// appears in more than one cycle; intersecting cycles are merged into one aggregate cycle.
+fn find_cycles(size: usize, consumers_of: &dyn Fn(usize) -> Vec<usize>) -> HashMap<usize, HashSet<usize>> {
+    let mut cycles: Vec<HashSet<usize>> = vec![];
+    cycles_visitor(0, size, consumers_of, &mut vec![], &mut HashSet::new(), &mut cycles);
+    let mut map = HashMap::new();
+    for cycle in cycles {
+        for ns in &cycle { map.insert(*ns, cycle.clone()); }
+    }
+    map
+}
+
+/// Performs a recursive aggregate cycles algorithm for a starting namespace, given the current recursion path,
+/// set of visited namespaces, and current set of cycles.
+///
+/// For each consumer of the namespace, check to see if it exists in the current recursion path.
+/// If it does, the path between the two namespaces either needs to be merged into an existing cycle
+/// or added as a new cycle.
+/// This is a depth-first search algorithm.
+/// Once every consumer of the namespace is visited, the namespace is marked visited and the algorithm will
+/// ascend back to the previous namespace.
+/// If there are no namespaces left in the chain, the algorithm moves to the next namespace in the graph and starts
+/// a new chain.
+/// The algorithm is finished once every namespace in the graph has been visited, either by recursion from
+/// an existing namespace or by iteration.
+fn cycles_visitor(
+    ns: usize,
+    size: usize,
+    consumers_of: &dyn Fn(usize) -> Vec<usize>,
+    current_path: &mut Vec<usize>,
+    visited: &mut HashSet<usize>,
+    cycles: &mut Vec<HashSet<usize>>
+) {
+    // break recursion if we're out of namespaces or the namespace has already been visited
+    if ns >= size || visited.contains(&ns) { return; }
 
-        // namespace is now visited
-        current_path.pop();
-        visited.insert(ns);
-        if current_path.len() == 0 {
-            // starting namespace in the path is finished, increment namespace number and recurse
-            self.cycles_visitor(ns + 1, current_path, visited, cycles);
+    current_path.push(ns);
+    for consumer in consumers_of(ns) {
+        if consumer == ns {
+            // TODO: figure out how to get diagnostics here (possibly just add another method to check for this)
+            // for posterity: if this is true, the namespace has a pure forward to itself, which should be just a warning
+        } else if let Some(consumer_index) = current_path.iter().position(|&n| n == consumer) {
+            // we have a cycle, gather all namespaces in the path
+            let cycle: HashSet<usize> = current_path[consumer_index..].iter().cloned().collect();
+            // check to see if there is an existing cycle containing ANY of them
+            if let Some(existing_cycle) = cycles.iter_mut().find(|c| cycle.iter().any(|n| c.contains(n))) {
+                // existing cycle, merge all of these into it
+                existing_cycle.extend(cycle);
+            } else {
+                // no existing cycle, add one
+                cycles.push(cycle);
+            }
+        } else {
+            // no cycle, recurse to consumer
+            cycles_visitor(consumer, size, consumers_of, current_path, visited, cycles);
         }
     }
+
+    // namespace is now visited
+    current_path.pop();
+    visited.insert(ns);
+    if current_path.len() == 0 {
+        // starting namespace in the path is finished, increment namespace number and recurse
+        cycles_visitor(ns + 1, size, consumers_of, current_path, visited, cycles);
+    }
 }
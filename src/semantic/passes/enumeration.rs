@@ -1,20 +1,57 @@
-use std::{path::{Path, PathBuf}, collections::{HashMap, VecDeque}};
-use super::dependencies::PureForward;
-use crate::parser::parse_module;
+use std::{path::{Path, PathBuf}, collections::HashMap};
+use super::dependencies::{PureForward, PureImport, SubmoduleReference};
+use super::loading::{self, FsModuleLoader};
 use crate::core::{ Diagnostic, DiagResult };
 use crate::syntax;
 use crate::semantic::namespace as ns;
 
 pub struct NamespaceEnumerationOutput {
-    modules: HashMap<&'static Path, ns::ModuleRef>,
-    namespaces: Vec<ns::Namespace>,
-    declarations: Vec<ns::Declaration>,
-    pure_forwards: Vec<PureForward>, // TODO: try to integrate this into the namespaces
-    diagnostics: Vec<Diagnostic>,
+    pub modules: HashMap<&'static Path, ns::ModuleRef>,
+    pub namespaces: Vec<ns::Namespace>,
+    pub declarations: Vec<ns::Declaration>,
+    pub pure_forwards: Vec<PureForward>, // TODO: try to integrate this into the namespaces
+    // TODO: populate alongside pure_forwards/pure_imports once `submodule X { .. }` and
+    // `import submodule X` are reachable from ModuleRoot - see handle_module below.
+    pub submodule_references: Vec<SubmoduleReference>,
+    // TODO: populate once `import from "mod" : *` is reachable from ModuleRoot - see
+    // handle_module below, and `Import::Glob` in the import grammar.
+    pub pure_imports: Vec<PureImport>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl NamespaceEnumerationOutput {
+    /// An empty registry, as if enumeration had run over zero modules - the "prior" a caller
+    /// with no session yet (the REPL's first submitted line) hands to
+    /// `enumerate_namespaces_incremental` so it doesn't need a separate from-scratch entry point.
+    pub fn empty() -> NamespaceEnumerationOutput {
+        NamespaceEnumerationOutput {
+            modules: HashMap::new(),
+            namespaces: vec![],
+            declarations: vec![],
+            pure_forwards: vec![],
+            submodule_references: vec![],
+            pure_imports: vec![],
+            diagnostics: vec![],
+        }
+    }
+}
+
+pub fn enumerate_namespaces(main_module_path: PathBuf, trace_parse: bool) -> DiagResult<NamespaceEnumerationOutput> {
+    let loader = FsModuleLoader { trace_parse };
+    EnumerationProcess::new(main_module_path, trace_parse).run(&loader)
 }
 
-pub fn enumerate_namespaces(main_module_path: PathBuf) -> DiagResult<NamespaceEnumerationOutput> {
-    return EnumerationProcess::new(main_module_path).run();
+/// Enumerates one more module - typically a REPL line's synthetic in-memory source, fed
+/// through a caller-supplied `loader` rather than `FsModuleLoader` - on top of an
+/// already-enumerated registry, instead of starting over from scratch. See
+/// `EnumerationProcess::resume` for what carries over and what doesn't.
+pub fn enumerate_namespaces_incremental(
+    main_module_path: PathBuf,
+    trace_parse: bool,
+    prior: NamespaceEnumerationOutput,
+    loader: &dyn loading::ModuleLoader,
+) -> DiagResult<NamespaceEnumerationOutput> {
+    EnumerationProcess::resume(main_module_path, trace_parse, prior).run(loader)
 }
 
 enum Declaration {
@@ -26,47 +63,88 @@ enum Declaration {
 }
 
 struct EnumerationProcess {
-    module_queue: VecDeque<PathBuf>,
+    main_module_path: PathBuf,
     modules: HashMap<&'static Path, ns::ModuleRef>,
     namespaces: Vec<ns::Namespace>,
     declarations: Vec<ns::Declaration>,
     pure_forwards: Vec<PureForward>,
+    submodule_references: Vec<SubmoduleReference>,
+    pure_imports: Vec<PureImport>,
     diagnostics: Vec<Diagnostic>,
+    /// Forwarded to the module loader, enabling `--trace-parse` grammar-debugging output for
+    /// each module as it's loaded.
+    trace_parse: bool,
 }
 
 impl EnumerationProcess {
-    fn new(main_module_path: PathBuf) -> EnumerationProcess {
-        let process = EnumerationProcess {
-            module_queue: VecDeque::new(),
+    fn new(main_module_path: PathBuf, trace_parse: bool) -> EnumerationProcess {
+        EnumerationProcess {
+            main_module_path,
             modules: HashMap::new(),
             namespaces: vec![],
             declarations: vec![],
             pure_forwards: vec![],
+            submodule_references: vec![],
+            pure_imports: vec![],
             diagnostics: vec![],
-        };
-        process.module_queue.push_back(main_module_path);
-        process.modules.insert(main_module_path.as_ref(), ns::ModuleRef::Referenced { fullyResolved: false });
-        process
+            trace_parse,
+        }
     }
 
-    fn run(&mut self) -> DiagResult<NamespaceEnumerationOutput> {
-        self.consume_module_queue()?;
-        DiagResult::ok(self.output())
+    /// Like `new`, but seeded from a prior `NamespaceEnumerationOutput` instead of starting
+    /// empty - lets a caller that already enumerated some modules (the REPL's accumulating
+    /// session; eventually `analyze_incremental`'s unchanged-module reuse) enumerate one more
+    /// without re-enumerating everything it already has. Diagnostics do not carry over - the
+    /// prior run already reported its own, so this run's output is only the new module's.
+    fn resume(main_module_path: PathBuf, trace_parse: bool, prior: NamespaceEnumerationOutput) -> EnumerationProcess {
+        EnumerationProcess {
+            main_module_path,
+            modules: prior.modules,
+            namespaces: prior.namespaces,
+            declarations: prior.declarations,
+            pure_forwards: prior.pure_forwards,
+            submodule_references: prior.submodule_references,
+            pure_imports: prior.pure_imports,
+            diagnostics: vec![],
+            trace_parse,
+        }
     }
 
-    fn consume_module_queue(&mut self) -> DiagResult<()> {
-        if self.module_queue.is_empty() { return DiagResult::ok(()); }
-
-        let module_path = self.module_queue.pop_front().unwrap();
-        // parse the module
-        let module_syntax = match parse_module(module_path) {
-            DiagResult(Some(module_syntax), diags) => module_syntax,
-            DiagResult(None, diags) => {
-                todo!()
-            }
-        };
+    /// Loading is a distinct up-front phase: every module reachable from the entry point is
+    /// resolved and parsed, landing in a terminal `ModuleRef::Success`/`Unparsed` state and
+    /// earning at most one "could not be resolved" diagnostic per referencing site, before
+    /// this process builds a single namespace from any of them. That ordering is what lets
+    /// `handle_module` below (and, later, dependency resolution) simply trust `self.modules`
+    /// instead of re-deriving a module's status inline.
+    ///
+    /// `loader` is taken as a parameter, rather than always constructing an `FsModuleLoader`
+    /// here, so a caller like the REPL can hand in a loader that serves an in-memory line of
+    /// source instead of reading a file.
+    fn run(&mut self, loader: &dyn loading::ModuleLoader) -> DiagResult<NamespaceEnumerationOutput> {
+        let loading::LoadedModules { modules, parsed, diagnostics } =
+            loading::load_module_graph_incremental(self.main_module_path.clone(), loader, std::mem::take(&mut self.modules));
+        self.modules = modules;
+        self.diagnostics.extend(diagnostics);
+        for (module_path, module_syntax) in parsed {
+            self.handle_module(module_path, module_syntax);
+        }
+        DiagResult::ok(self.output())
+    }
 
-        todo!()
+    /// Registers a successfully-loaded module's namespace. Processing the module's own
+    /// imports/exports/declarations into dependencies is handled by a later pass.
+    ///
+    /// TODO: once `submodule X { .. }` is reachable from `ModuleRoot`, each one becomes a
+    /// `Namespace::new_submodule` nested under this module (or under an enclosing submodule),
+    /// and each `import submodule X`/`submodule A = F X` it contains becomes a
+    /// `SubmoduleReference` pushed onto `self.submodule_references`, for the dependency-ordered
+    /// resolution pass to consume.
+    fn handle_module(&mut self, module_path: &'static Path, _module_syntax: syntax::ModuleRoot) {
+        let namespace_id: ns::NamespaceId = self.namespaces.len();
+        self.namespaces.push(ns::Namespace::new_module(namespace_id, module_path.to_path_buf()));
+        // TODO: walk module_syntax.exports/forwards/declarations to populate this namespace's
+        // dependencies - loading (above) already resolved and queued every module this one
+        // imports or forwards, so this no longer needs to touch `self.modules` at all.
     }
 
     fn output(self) -> NamespaceEnumerationOutput {
@@ -75,6 +153,8 @@ impl EnumerationProcess {
             namespaces: self.namespaces,
             declarations: self.declarations,
             pure_forwards: self.pure_forwards,
+            submodule_references: self.submodule_references,
+            pure_imports: self.pure_imports,
             diagnostics: self.diagnostics
         }
     }
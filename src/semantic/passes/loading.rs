@@ -0,0 +1,181 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use crate::core::{Diagnostic, DiagnosticCode, DiagResult, FilePosition, FileRange};
+use crate::parser::parse_module;
+use crate::syntax::ModuleRoot;
+use crate::semantic::namespace::ModuleRef;
+use crate::semantic::suggest::find_best_match;
+
+/// Turns a module path into parsed syntax. Pulled out as a trait, rather than enumeration
+/// calling `parse_module` directly, so the transitive load below can be swapped for a batched
+/// or parallel implementation (or a canned in-memory one in tests) without touching the walk
+/// itself.
+pub trait ModuleLoader {
+    fn load(&self, path: &Path) -> DiagResult<ModuleRoot>;
+}
+
+/// The production loader: parses straight off disk via `parse_module`.
+pub struct FsModuleLoader {
+    /// Forwarded to every `parse_module` call, enabling `--trace-parse` grammar-debugging
+    /// output for each module as it's loaded.
+    pub trace_parse: bool,
+}
+
+impl ModuleLoader for FsModuleLoader {
+    fn load(&self, path: &Path) -> DiagResult<ModuleRoot> {
+        parse_module(path, self.trace_parse)
+    }
+}
+
+/// Serves a single module's already-known source text from memory instead of reading it off
+/// disk - the REPL's "canned in-memory" case this module's own doc comment above anticipated.
+/// A REPL session builds a new one for every submitted line, serving the whole session's source
+/// so far under one fixed synthetic path, parsed through the same `Parser` `parse_module` itself
+/// uses.
+///
+/// This only works as long as `import_targets` (below) doesn't yet resolve real
+/// import statements: the transitive load this loader participates in never reaches past the
+/// one module it was constructed with, so there's no second path it could ever be asked for.
+pub struct StringModuleLoader {
+    pub path: PathBuf,
+    pub source: String,
+    pub trace_parse: bool,
+}
+
+impl ModuleLoader for StringModuleLoader {
+    fn load(&self, path: &Path) -> DiagResult<ModuleRoot> {
+        let mut parser = crate::parser::parser_new::Parser::new::<ModuleRoot>().with_trace(self.trace_parse);
+        parser.parse(path, self.source.clone())
+    }
+}
+
+/// Every module the transitive load reached, in the order it was first loaded (so a
+/// `ModuleRef::Success::namespaceId` can simply be this module's index here - the same
+/// convention `EnumerationProcess::handle_module` used when it did this loading inline).
+pub struct LoadedModules {
+    pub modules: HashMap<&'static Path, ModuleRef>,
+    pub parsed: Vec<(&'static Path, ModuleRoot)>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// One entry of the loader's work queue: the path to load, and the location of the
+/// import/forward that referenced it (`None` for the main module, which nothing references).
+struct WorkItem {
+    path: PathBuf,
+    referenced_from: Option<FileRange>,
+}
+
+/// Computes the transitive closure of modules reachable from `main_module_path` and loads
+/// every one of them through `loader`, up front, before any namespace or dependency is built
+/// from them. This mirrors ECMAScript's LoadRequestedModules-before-ResolveExport ordering:
+/// by the time this returns, every module the program can reach is in a terminal
+/// `ModuleRef::Success`/`Unparsed` state, keyed once by its path, and nothing downstream needs
+/// to re-derive that state or re-diagnose a module as missing.
+///
+/// A module that fails to load is still recorded (as `Unparsed`) rather than dropped, so every
+/// site that referenced it can be attributed its own "could not be resolved" diagnostic instead
+/// of the failure being reported only once, arbitrarily, at whichever reference was discovered
+/// first.
+pub fn load_module_graph(main_module_path: PathBuf, loader: &dyn ModuleLoader) -> LoadedModules {
+    load_module_graph_incremental(main_module_path, loader, HashMap::new())
+}
+
+/// Like `load_module_graph`, but starting from an already-populated `modules` registry instead
+/// of an empty one, so a module already loaded in a prior call is recognized as such (the
+/// `modules.contains_key` check below) instead of being fetched and parsed again. This is what
+/// lets the REPL's accumulating session, and eventually `analyze_incremental`'s unchanged-module
+/// reuse, enumerate one more module on top of what's already there.
+pub fn load_module_graph_incremental(
+    main_module_path: PathBuf,
+    loader: &dyn ModuleLoader,
+    prior_modules: HashMap<&'static Path, ModuleRef>,
+) -> LoadedModules {
+    let mut modules = prior_modules;
+    let mut parsed = vec![];
+    let mut diagnostics = vec![];
+    let mut module_references: Vec<(PathBuf, FileRange)> = vec![];
+    let mut queue = VecDeque::new();
+    queue.push_back(WorkItem { path: main_module_path.clone(), referenced_from: None });
+
+    while let Some(WorkItem { path, referenced_from }) = queue.pop_front() {
+        if modules.contains_key(path.as_path()) {
+            if let Some(location) = referenced_from {
+                module_references.push((path, location));
+            }
+            continue;
+        }
+
+        match loader.load(&path) {
+            DiagResult(Some(module), diags) => {
+                diagnostics.extend(diags);
+                let namespace_id = parsed.len();
+                modules.insert(path.as_ref(), ModuleRef::Success { namespaceId: namespace_id, fullyResolved: false });
+                for (target_path, target_location) in import_targets(&module) {
+                    queue.push_back(WorkItem { path: target_path, referenced_from: Some(target_location) });
+                }
+                parsed.push((path.as_ref(), module));
+            }
+            DiagResult(None, diags) => {
+                diagnostics.extend(diags);
+                modules.insert(path.as_ref(), ModuleRef::Unparsed { fullyResolved: true });
+                if path == main_module_path {
+                    // the entry point is never imported by anything else, so there's no
+                    // import site to attribute this to: report it directly and stop
+                    diagnostics.push(Diagnostic::new_from_position(
+                        format!("Entry point \"{}\" failed to parse.", path.display()),
+                        FilePosition::new(path, (0, 0))
+                    ).with_code(DiagnosticCode::RenError("REN0001")));
+                    return LoadedModules { modules, parsed, diagnostics };
+                }
+            }
+        }
+
+        if let Some(location) = referenced_from {
+            module_references.push((path, location));
+        }
+    }
+
+    attribute_failed_module_diagnostics(&modules, &module_references, &mut diagnostics);
+    LoadedModules { modules, parsed, diagnostics }
+}
+
+/// Once the queue has fully drained, every module is in one of its terminal states. Walk
+/// every recorded reference and emit one diagnostic per site that points at a module which
+/// ended up `Unparsed` (the main module's own failure, if any, was already reported directly
+/// in `load_module_graph`).
+fn attribute_failed_module_diagnostics(
+    modules: &HashMap<&'static Path, ModuleRef>,
+    module_references: &[(PathBuf, FileRange)],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for (target, location) in module_references {
+        let failed = matches!(modules.get(target.as_path()), Some(ModuleRef::Unparsed { .. }));
+        if failed {
+            let candidates: Vec<String> = modules.keys().map(|p| p.to_string_lossy().into_owned()).collect();
+            diagnostics.push(Diagnostic::new(
+                format!("Module \"{}\" could not be resolved", target.display()),
+                location.clone()
+            )
+                .with_did_you_mean(find_best_match(&target.to_string_lossy(), candidates.iter().map(String::as_str)))
+                .with_code(DiagnosticCode::RenError("REN0002")));
+        }
+    }
+}
+
+/// Resolves every import/forward of `module` to an absolute path and the location of the
+/// name that references it.
+///
+/// Always empty today: `ModuleRoot` doesn't parse imports/forwards at all yet (see its own doc
+/// comment), so there's no real declaration here to walk, even though `syntax::ImportDeclaration`
+/// now carries real fields (`module_name`, `imports`, `integrity`) for when it can. Until then,
+/// every module this crate loads looks like a leaf with no dependencies - `load_module_graph`'s
+/// transitive closure, `attribute_failed_module_diagnostics`'s "could not be resolved" reporting,
+/// and the worklist this feeds in `resolution.rs` are all real and wired to
+/// `LoadedModules`/`ModuleLoader`, but none of them currently sees a dependency at all - same
+/// limitation as
+/// `loader::import_targets`, which this duplicates until one of the two call sites is retired.
+/// Once `ModuleRoot` exposes its real imports/forwards, each target name should be resolved via
+/// `resolver::resolve_module` relative to `module`'s own path.
+fn import_targets(_module: &ModuleRoot) -> Vec<(PathBuf, FileRange)> {
+    vec![]
+}
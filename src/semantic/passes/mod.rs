@@ -0,0 +1,6 @@
+pub mod dependencies;
+pub mod enumeration;
+pub mod loading;
+pub mod name_clash;
+pub mod resolution;
+pub mod typecheck;
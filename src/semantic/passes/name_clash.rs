@@ -0,0 +1,17 @@
+use crate::core::Diagnostic;
+use crate::semantic::namespace::{Declaration, Namespace};
+
+pub struct NameClashOutput {
+    pub declarations: Vec<Declaration>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Pass 4 - Name Clash Checking (see `semantic::mod`'s pipeline doc comment).
+///
+/// Not yet implemented: no declaration has been checked against its same-named siblings for a
+/// legal overload/merge yet. Until then this is a no-op passthrough, so `resolve_and_check` has
+/// something to call and pass 3's result still reaches `Program` - not a stand-in for the real
+/// pass, just enough for the pipeline to compile and run end to end ahead of it being written.
+pub fn check_name_clashes(declarations: Vec<Declaration>, _namespaces: &[Namespace]) -> NameClashOutput {
+    NameClashOutput { declarations, diagnostics: vec![] }
+}
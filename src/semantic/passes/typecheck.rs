@@ -0,0 +1,17 @@
+use crate::core::Diagnostic;
+use crate::semantic::namespace::{Declaration, Namespace};
+
+pub struct TypecheckOutput {
+    pub declarations: Vec<Declaration>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Pass 3 - Type Checking (see `semantic::mod`'s pipeline doc comment).
+///
+/// Not yet implemented: every declaration's type still needs inferring/checking against its
+/// uses. Until then this is a no-op passthrough, so `resolve_and_check` has something to call
+/// and passes 1-2's result still reaches `Program` - not a stand-in for the real pass, just
+/// enough for the pipeline to compile and run end to end ahead of it being written.
+pub fn typecheck(declarations: Vec<Declaration>, _namespaces: &[Namespace]) -> TypecheckOutput {
+    TypecheckOutput { declarations, diagnostics: vec![] }
+}
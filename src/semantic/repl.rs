@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+use crate::core::{DiagResult, Diagnostic, DiagnosticLevel};
+use super::passes::enumeration::{enumerate_namespaces_incremental, NamespaceEnumerationOutput};
+use super::passes::loading::StringModuleLoader;
+use super::resolve_and_check;
+
+/// Synthetic module path every REPL line is parsed under. Enumeration keys modules by path, so
+/// reusing the same path on every submission (rather than minting a fresh one per line) is what
+/// lets a later declaration shadow an earlier one with the same name, the way `MergePolicy`
+/// already governs for two declarations landing in the same module.
+const REPL_MODULE_PATH: &str = "<repl>";
+
+/// A persistent REPL session: keeps every line submitted so far, so each new line is analyzed
+/// against the accumulated session instead of in isolation.
+///
+/// `enumerate_namespaces_incremental` and `StringModuleLoader` exist so a session like this can
+/// seed enumeration from a prior run's registry instead of starting empty - the path this was
+/// built to use. But `handle_module` (enumeration.rs) doesn't walk a module's own
+/// imports/exports/declarations yet (still a TODO there), so there is no per-module state worth
+/// carrying forward: a second call over the same path would just find the module already in the
+/// registry and enumerate nothing new from it. Until that TODO is resolved, this instead
+/// re-synthesizes the whole session's source as one module and re-enumerates it from scratch on
+/// every line, which gives the same "new declarations shadow prior ones" result true incremental
+/// reuse would once declarations are real, just without the performance benefit.
+pub struct ReplSession {
+    lines: Vec<String>,
+    trace_parse: bool,
+}
+
+impl ReplSession {
+    pub fn new(trace_parse: bool) -> ReplSession {
+        ReplSession { lines: vec![], trace_parse }
+    }
+
+    /// Submits one more line of source to the session, re-analyzes the accumulated buffer, and
+    /// returns this line's diagnostics without aborting the session - a line whose analysis
+    /// reports an error is dropped from the buffer and simply contributes no new bindings,
+    /// rather than poisoning everything submitted before it.
+    pub fn submit_line(&mut self, line: String) -> Vec<Diagnostic> {
+        self.lines.push(line);
+        let path = PathBuf::from(REPL_MODULE_PATH);
+        let loader = StringModuleLoader {
+            path: path.clone(),
+            source: self.lines.join("\n"),
+            trace_parse: self.trace_parse,
+        };
+
+        let DiagResult(enumeration, enum_diags) = enumerate_namespaces_incremental(
+            path,
+            self.trace_parse,
+            NamespaceEnumerationOutput::empty(),
+            &loader,
+        );
+
+        let (program, mut diagnostics) = match enumeration {
+            Some(enumeration) => {
+                let DiagResult(program, check_diags) = resolve_and_check(enumeration);
+                (program, check_diags)
+            }
+            None => (None, vec![]),
+        };
+        diagnostics.splice(0..0, enum_diags);
+
+        // `resolve_and_check` always yields a program, so a line is also dropped when its
+        // analysis reports an error - otherwise it would stay in the buffer and its errors
+        // would come back with every later line.
+        let failed = diagnostics.iter().any(|d| d.level == DiagnosticLevel::Error || d.level == DiagnosticLevel::Fatal);
+        if program.is_none() || failed {
+            self.lines.pop();
+        }
+        diagnostics
+    }
+}
@@ -1,28 +1,84 @@
 use std::path::PathBuf;
+use crate::core::{Diagnostic, FileRange};
 
+/// A minimal stand-in for a fetchable URL. This only captures the string form; actually
+/// fetching a `Remote` module's contents is left to the module loader that consumes this.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Url(String);
+
+impl Url {
+    /// Parses `raw` as a URL if it looks like one (has a scheme), otherwise returns `None`.
+    pub fn parse(raw: &str) -> Option<Url> {
+        if raw.contains("://") { Some(Url(raw.to_owned())) } else { None }
+    }
+
+    pub fn as_str(&self) -> &str { &self.0 }
+}
+
+/// Where a module ultimately came from. This doubles as the capability the module was
+/// resolved with: a module loaded from `Remote` was fetched over the network and must not
+/// be able to read back into the local filesystem or process environment that hosts the
+/// compiler, while a `Local` module may import anything.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportLocation {
+    Local(PathBuf),
+    Remote(Url),
+    Env(String),
+    Missing,
+}
+
+/// Given the location of the module doing the importing and the literal path it imports,
+/// resolve the location of that import. `location` is the source range of the import path
+/// literal, used to report a diagnostic if the importing module isn't allowed to make this
+/// kind of import.
+pub fn resolve_module(from: ImportLocation, path: String, location: FileRange) -> (ImportLocation, Option<Diagnostic>) {
+    if let Some(url) = Url::parse(&path) {
+        return (ImportLocation::Remote(url), None);
+    }
+    if let Some(var_name) = path.strip_prefix("env:") {
+        if let ImportLocation::Remote(_) = from {
+            return (ImportLocation::Missing, Some(Diagnostic::new(
+                format!("A remote module cannot import the environment variable \"{}\"", var_name),
+                location
+            )));
+        }
+        return (ImportLocation::Env(var_name.to_owned()), None);
+    }
+    // anything left is a path import, which only makes sense relative to a local module
+    let from_path = match from {
+        ImportLocation::Local(from_path) => from_path,
+        _ => return (ImportLocation::Missing, Some(Diagnostic::new(
+            format!("A remote module cannot import the local path \"{}\"", path),
+            location
+        ))),
+    };
+    match resolve_local_module(from_path, path) {
+        Some(resolved) => (ImportLocation::Local(resolved), None),
+        None => (ImportLocation::Missing, None),
+    }
+}
 
 /// Given a path of a module imported into this module,
 /// resolve the absolute path of that module.
-pub fn resolve_module(from: PathBuf, path: String) -> Option<PathBuf> {
+fn resolve_local_module(from: PathBuf, path: String) -> Option<PathBuf> {
     // if it is a relative path, resolve the relation and determine if it exists
     if path.starts_with('.') {
-        let resolved = from.parent().unwrap().join(path);
+        let resolved = from.parent().unwrap().join(&path);
         return resolve_direct_path(resolved);
     }
-    // otherwise, it is a package import
-    let dir = from.parent();
-    while dir.is_some() {
-        let dir = dir.unwrap();
+    // otherwise, it is a package import: walk up from `from`'s directory toward the fs root,
+    // checking '{dir}/packages/{path}' at each level, the same lookup Node's CommonJS resolver
+    // uses for bare package specifiers
+    let mut dir = from.parent();
+    while let Some(current) = dir {
         // we want to check the path '{currentModuleDir}/packages/{importPath}' for a valid module
-        let resolved = resolve_direct_path(dir.join("packages").join(path));
+        let resolved = resolve_direct_path(current.join("packages").join(&path));
         // valid path, use it
         if resolved.is_some() { return resolved; }
         // if it didn't exist, we want to continue to check parent directories until we reach the fs root
-        let parent = dir.parent();
-        if parent.is_none() { break; }
-        dir = parent.unwrap();
+        dir = current.parent();
     }
-    return None;
+    None
 }
 
 /// Given an absolute path to an imported module (it may not exist),
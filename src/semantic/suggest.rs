@@ -0,0 +1,66 @@
+/// "Did you mean" suggestions for dangling references, modeled on rustc_resolve's own
+/// edit-distance fallback (`find_best_match_for_name`): given the name that failed to
+/// resolve and the full set of candidates that were actually in scope, propose the closest
+/// one by Levenshtein distance, if anything is close enough to plausibly be a typo.
+
+/// Finds the candidate in `candidates` closest to `name` by edit distance, accepting only a
+/// match within `max(1, name.len() / 3)` edits of it - close enough to plausibly be a typo,
+/// rather than an unrelated name that happens to share a few characters. Ties break on the
+/// shorter candidate, then lexical order, so the result is deterministic.
+pub(crate) fn find_best_match<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<String> {
+    let threshold = std::cmp::max(1, name.len() / 3);
+    let mut best: Option<(usize, &'a str)> = None;
+
+    for candidate in candidates {
+        if candidate == name { continue; }
+        let distance = match bounded_edit_distance(name, candidate, threshold) {
+            Some(distance) => distance,
+            None => continue,
+        };
+        let is_better = match best {
+            None => true,
+            Some((best_distance, best_candidate)) => {
+                distance < best_distance
+                    || (distance == best_distance && (candidate.len(), candidate) < (best_candidate.len(), best_candidate))
+            }
+        };
+        if is_better {
+            best = Some((distance, candidate));
+        }
+    }
+
+    best.map(|(_, candidate)| candidate.to_owned())
+}
+
+/// Levenshtein distance between `a` and `b`, bailing out early with `None` once every entry
+/// in the row currently being computed has already exceeded `max` - a row that's entirely
+/// over budget can only get worse on every subsequent row, so there's no reason to finish
+/// the rest of the table. This keeps a large candidate set cheap to scan.
+fn bounded_edit_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.len().abs_diff(b.len()) > max { return None; }
+
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut current_row = vec![0usize; b.len() + 1];
+        current_row[0] = i;
+        let mut row_min = current_row[0];
+
+        for j in 1..=b.len() {
+            let substitution_cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            current_row[j] = std::cmp::min(
+                std::cmp::min(current_row[j - 1] + 1, previous_row[j] + 1),
+                previous_row[j - 1] + substitution_cost
+            );
+            row_min = std::cmp::min(row_min, current_row[j]);
+        }
+
+        if row_min > max { return None; }
+        previous_row = current_row;
+    }
+
+    let distance = previous_row[b.len()];
+    if distance > max { None } else { Some(distance) }
+}
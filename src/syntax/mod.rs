@@ -1,10 +1,9 @@
 use crate::parser::parser_new::{ParseResult, ParseState, ParseFunc};
 use crate::parser::primitives::{choice, repeat, eof, RepeatBase};
-use crate::{core::{FilePosition, FileRange}, seq};
+use crate::parser::lexer::Trivia;
+use crate::{core::FileRange, seq};
 use std::any::Any;
 
-//mod declarations;
-//pub mod environment;
 //mod expressions;
 //mod module_root;
 //mod parsing;
@@ -23,10 +22,44 @@ use std::any::Any;
 
 //pub use visitor::*;
 
-pub trait Syntax: Any + Sized {
+/// `Clone` is a supertrait (not just a bound where needed) because `parse::<T>()` feeds every
+/// `Syntax` type through `bk_memo`'s type-erased cache, and a memo entry has to stay usable
+/// for a later cache hit at the same key instead of being consumed by the first one.
+pub trait Syntax: Any + Sized + Clone {
     fn parse_func() -> ParseFunc<Self>;
     fn location(&self) -> FileRange;
     fn syntax_type(&self) -> SyntaxType;
+
+    /// Whitespace and newlines immediately preceding this node, in source order; comments are
+    /// not captured as trivia. Following rust-analyzer's lossless syntax-tree design, every node
+    /// owns the trivia nearest to it rather than the lexer discarding it between tokens, so a
+    /// tree built from `Syntax` nodes can be reprinted via `to_source` without losing formatting.
+    fn leading_trivia(&self) -> &[Trivia];
+
+    /// Whitespace and newlines immediately following this node, in source order. See
+    /// `leading_trivia` - `primitives::with_trivia` takes trailing trivia after every node, so
+    /// trivia between two sibling nodes is attributed to the earlier node's `trailing_trivia`
+    /// (the next node's `leading_trivia` is then empty), never split between the two.
+    fn trailing_trivia(&self) -> &[Trivia];
+
+    /// Returns `self` with `leading`/`trailing` attached as this node's surrounding trivia.
+    /// Called by `primitives::with_trivia` once a node has finished parsing, so `parse::<T>()`
+    /// never has to reach into a node's fields itself to attach what surrounds it.
+    fn with_surrounding_trivia(self, leading: Vec<Trivia>, trailing: Vec<Trivia>) -> Self;
+}
+
+/// Reproduces the original source text a node was parsed from, byte-for-byte, by walking its
+/// leading trivia, its own text, and its trailing trivia.
+///
+/// This only accounts for trivia attached directly to `node` itself - reconstructing a whole
+/// file requires recursing into each node's children and concatenating their own `to_source`
+/// output in between, which is left to each `Syntax` impl (via `accept`/its visitor) once the
+/// grammar in this module is fully ported, since this module has no single `Node` union to
+/// recurse over generically yet.
+pub fn to_source<T: Syntax>(node: &T, own_text: &str) -> String {
+    let leading: String = node.leading_trivia().iter().map(|t| t.image()).collect();
+    let trailing: String = node.trailing_trivia().iter().map(|t| t.image()).collect();
+    format!("{}{}{}", leading, own_text, trailing)
 }
 
 /// The full enumeration of types of syntax nodes in the language.
@@ -95,14 +128,36 @@ pub enum SyntaxType {
     TypeParam,
     Param,
     // #endregion
+    /// Produced by the `recover()` combinator in place of a declaration/statement/expression
+    /// that failed to parse, spanning whatever was skipped to resynchronize.
+    ErrorNode,
+    // #region trivia
+    /// A line or block comment as trivia. `primitives::with_trivia` does not produce this
+    /// kind; it captures only whitespace and newlines.
+    Comment,
+    /// A run of insignificant whitespace (including newlines) captured as trivia.
+    Whitespace,
+    // #endregion
 }
 
+/// `parse_func` below is the one and only top-level entry point for parsing a whole `.ren`
+/// file, but it doesn't actually run yet: `non_term::<ImportDeclaration>()` requires
+/// `ImportDeclaration: Syntax`, which isn't implemented (no `parse_func`/`location`/trivia
+/// methods on it), and `choice::<NonImport>()` names a `NonImport` type - the union of
+/// exports/forwards/declarations a module can otherwise contain - that doesn't exist anywhere
+/// in this crate yet. Both are the same "declaration grammar" gap `golden.rs`'s `run_suite`
+/// doc comment and `loader`/`loading`'s `import_targets` stubs already call out; `eof`/`choice`
+/// themselves (see `primitives.rs`) are real now, but there's no grammar built on top of them
+/// here yet for that to unblock.
+#[derive(Clone)]
 pub struct ModuleRoot {
     location: FileRange,
     imports: Vec<ImportDeclaration>,
     exports: Vec<ExportDeclaration>,
     forwards: Vec<ExportForwardDeclaration>,
     declarations: Vec<Declaration>,
+    leading_trivia: Vec<Trivia>,
+    trailing_trivia: Vec<Trivia>,
 }
 
 impl Syntax for ModuleRoot {
@@ -114,33 +169,131 @@ impl Syntax for ModuleRoot {
                 eof()
             ),
             |(imports, decls, eof)| {
-                let start_pos = FilePosition::new(eof.location.path, (0, 0));
+                let eof_range = eof.range();
                 let (exports, forwards, declarations) = decls.sort();
                 ModuleRoot {
-                    location: start_pos.merge(eof.location),
+                    location: FileRange::new(eof_range.path().to_path_buf(), (0, 0), eof_range.end()),
                     imports,
                     exports,
                     forwards,
                     declarations,
+                    // Overwritten by `with_surrounding_trivia` once `parse::<ModuleRoot>()`
+                    // finishes; this parse_func never runs outside of that wrapper.
+                    leading_trivia: vec![],
+                    trailing_trivia: vec![],
                 }
             }
         )
     }
 
-    fn location(&self) -> FileRange { self.location }
+    fn location(&self) -> FileRange { self.location.clone() }
     fn syntax_type(&self) -> SyntaxType { SyntaxType::ModuleRoot }
+    fn leading_trivia(&self) -> &[Trivia] { &self.leading_trivia }
+    fn trailing_trivia(&self) -> &[Trivia] { &self.trailing_trivia }
+
+    fn with_surrounding_trivia(mut self, leading: Vec<Trivia>, trailing: Vec<Trivia>) -> Self {
+        self.leading_trivia = leading;
+        self.trailing_trivia = trailing;
+        self
+    }
 }
 
 
+/// Stands in for a declaration, statement, or expression that `recover()` gave up on and
+/// skipped over. Carries only the span of text that was skipped to resynchronize - there's
+/// no parsed content to hold, since parsing it is exactly what failed.
+#[derive(Clone)]
+pub struct ErrorNode {
+    location: FileRange,
+}
+
+impl ErrorNode {
+    pub fn new(location: FileRange) -> ErrorNode {
+        ErrorNode { location }
+    }
+}
+
+/// Lets `recover` (generic over `T: From<FileRange>`) construct an `ErrorNode` from the span
+/// it skipped, without needing to know anything about `ErrorNode`'s shape.
+impl From<FileRange> for ErrorNode {
+    fn from(location: FileRange) -> ErrorNode {
+        ErrorNode::new(location)
+    }
+}
+
+impl Syntax for ErrorNode {
+    fn parse_func() -> ParseFunc<Self> { panic!("ErrorNode is only ever produced by recover(), never parsed directly") }
+    fn location(&self) -> FileRange { self.location.clone() }
+    fn syntax_type(&self) -> SyntaxType { SyntaxType::ErrorNode }
+    // the skipped span is kept only by `location`, with no separately-tracked trivia of its own
+    fn leading_trivia(&self) -> &[Trivia] { &[] }
+    fn trailing_trivia(&self) -> &[Trivia] { &[] }
+    fn with_surrounding_trivia(self, _leading: Vec<Trivia>, _trailing: Vec<Trivia>) -> Self { self }
+}
+
 pub mod environment {
     pub struct SyntaxEnvironment;
 }
-pub struct ImportDeclaration;
+/// One entry of an `ImportDeclaration`'s import list. Beyond a plain name (optionally aliased)
+/// and a whole-namespace `* as name` bind, an import can also be:
+/// - `Glob` - a bare `*`, bringing every export of the target module into local scope
+///   directly, unlike `Wildcard` which binds the whole namespace to one alias
+/// - `Path` - a dotted namespace path that walks into a nested namespace of the target module
+///   before importing `member`
+/// - `Group` - `{ a, b as c }`, selecting several members (themselves possibly paths/groups) out
+///   of a namespace in one import
+/// - `Hiding` - `* hiding (a, b)`, importing everything from a namespace except a named set
+///
+/// Plain data only - there's no grammar wired up yet that constructs one of these from real
+/// source (`ModuleRoot::parse_func` doesn't parse imports at all; see its own doc comment), so
+/// nothing currently builds an `Import` other than by hand. Kept here, on the type the crate
+/// actually compiles, rather than in a separate file no `mod` declaration ever pulled in.
+#[derive(Clone)]
+pub enum Import {
+    Name { import_name: String, alias_name: String },
+    Wildcard { alias_name: String },
+    Glob,
+    Path { segments: Vec<String>, member: Box<Import> },
+    Group { namespace_name: String, members: Vec<Import> },
+    Hiding { namespace_name: String, excluded: Vec<String> },
+}
+
+#[derive(Clone)]
+pub struct ImportDeclaration {
+    location: FileRange,
+    module_name: String,
+    imports: Vec<Import>,
+    /// An optional pinned content hash (e.g. `sha256:…`) trailing the module path. When
+    /// present, the loader is meant to verify the resolved module's own hash matches this
+    /// before using it, so an import can be pinned to a known-good version of a dependency -
+    /// see `cache::ImportCache::verify_integrity`.
+    integrity: Option<String>,
+}
+
+impl ImportDeclaration {
+    pub fn new(location: FileRange, module_name: String, imports: Vec<Import>, integrity: Option<String>) -> Self {
+        ImportDeclaration { location, module_name, imports, integrity }
+    }
+
+    pub fn module_name(&self) -> &str { &self.module_name }
+
+    pub fn imports(&self) -> &[Import] { &self.imports }
+
+    pub fn integrity(&self) -> Option<&str> { self.integrity.as_deref() }
+}
+#[derive(Clone)]
 pub struct ExportDeclaration;
+#[derive(Clone)]
 pub struct ExportForwardDeclaration;
+#[derive(Clone)]
 pub struct Declaration;
+#[derive(Clone)]
 pub struct AnonymousDeclaration;
+#[derive(Clone)]
 pub struct NamespaceDeclaration;
+#[derive(Clone)]
 pub struct FunctionDeclaration;
+#[derive(Clone)]
 pub struct TypeDeclaration;
+#[derive(Clone)]
 pub struct ConstantDeclaration;